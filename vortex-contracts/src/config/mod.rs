@@ -0,0 +1,417 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::events::{emit, VortexEvent};
+use crate::roles::{self, Role};
+use crate::utils::create_pda_account;
+use crate::VortexError;
+
+/// The config program, which owns the shared config and the role memberships
+/// read by the token, marketplace and governance programs
+pub mod config_program {
+    solana_program::declare_id!("3GB3GHeR6DnNiL2D5VQ5XqZC2XzVDqVPMdXJjz9ZJueK");
+}
+
+/// Seed of the shared `ProgramConfig` PDA, derived under `config_program`
+pub const CONFIG_SEED: &[u8] = b"config";
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum ConfigInstruction {
+    /// Create the shared program config
+    /// 
+    /// Only the upgrade authority of the config program can become admin.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The admin, the config program's upgrade authority
+    /// 1. `[writable]` The config account (PDA of `["config"]`)
+    /// 2. `[]` The config program's program data account
+    /// 3. `[]` The system program
+    /// 4. `[]` The rent sysvar
+    InitializeConfig {
+        guardian: Pubkey,
+    },
+
+    /// Pause or unpause a module
+    /// 
+    /// The admin may pause and unpause, the guardian may only pause.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The admin or guardian
+    /// 1. `[writable]` The config account
+    SetPaused {
+        module: PausableModule,
+        paused: bool,
+    },
+
+    /// Grant `role` to `member`
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The config admin
+    /// 1. `[]` The program config account (PDA of `["config"]`)
+    /// 2. `[writable]` The membership account (PDA of `["role", role, member]`)
+    /// 3. `[]` The system program
+    /// 4. `[]` The rent sysvar
+    GrantRole {
+        role: Role,
+        member: Pubkey,
+    },
+
+    /// Revoke `role` from `member`, refunding the record's rent to the admin
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The config admin
+    /// 1. `[]` The program config account (PDA of `["config"]`)
+    /// 2. `[writable]` The membership account
+    RevokeRole {
+        role: Role,
+        member: Pubkey,
+    },
+
+    /// Give up one of your own roles, refunding the record's rent to you
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The member
+    /// 1. `[writable]` The membership account
+    RenounceRole {
+        role: Role,
+    },
+}
+
+/// Operations that can be halted independently
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PausableModule {
+    /// Token movements through the token program
+    Token,
+    Listing,
+    Purchase,
+    Voting,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct PauseFlags {
+    pub token: bool,
+    pub listing: bool,
+    pub purchase: bool,
+    pub voting: bool,
+}
+
+impl PauseFlags {
+    pub fn is_paused(&self, module: PausableModule) -> bool {
+        match module {
+            PausableModule::Token => self.token,
+            PausableModule::Listing => self.listing,
+            PausableModule::Purchase => self.purchase,
+            PausableModule::Voting => self.voting,
+        }
+    }
+
+    pub fn set(&mut self, module: PausableModule, paused: bool) {
+        match module {
+            PausableModule::Token => self.token = paused,
+            PausableModule::Listing => self.listing = paused,
+            PausableModule::Purchase => self.purchase = paused,
+            PausableModule::Voting => self.voting = paused,
+        }
+    }
+}
+
+/// Configuration shared by the token, marketplace and governance modules
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub paused: PauseFlags,
+    pub bump: u8,
+}
+
+impl ProgramConfig {
+    pub const LEN: usize = 32 + 32 + 4 + 1;
+}
+
+/// Address of the shared config
+pub fn find_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &config_program::id())
+}
+
+/// Fail with `VortexError::ProgramPaused` if `module` is paused.
+///
+/// `config_account` must be the config PDA; before the config exists nothing
+/// is paused.
+pub fn assert_not_paused(
+    config_account: &AccountInfo,
+    module: PausableModule,
+) -> ProgramResult {
+    if *config_account.key != find_config_address().0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.data_is_empty() {
+        return Ok(());
+    }
+    if *config_account.owner != config_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let config = ProgramConfig::try_from_slice(&config_account.data.borrow())?;
+    if config.paused.is_paused(module) {
+        msg!("Module {:?} is paused", module);
+        return Err(VortexError::ProgramPaused.into());
+    }
+
+    Ok(())
+}
+
+/// Load the config PDA, which must already exist
+pub fn load_config(config_account: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    if *config_account.key != find_config_address().0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *config_account.owner != config_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(ProgramConfig::try_from_slice(&config_account.data.borrow())?)
+}
+
+// Fail unless `authority` is the upgrade authority recorded in `program_data`
+pub(crate) fn check_upgrade_authority(
+    program_id: &Pubkey,
+    program_data: &AccountInfo,
+    authority: &Pubkey,
+) -> ProgramResult {
+    let (program_data_key, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if *program_data.key != program_data_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *program_data.owner != bpf_loader_upgradeable::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let state: UpgradeableLoaderState = limited_deserialize(
+        &program_data.data.borrow(),
+        UpgradeableLoaderState::size_of_programdata_metadata() as u64,
+    )
+    .map_err(|_| ProgramError::InvalidAccountData)?;
+    match state {
+        UpgradeableLoaderState::ProgramData { upgrade_authority_address: Some(upgrade_authority), .. }
+            if upgrade_authority == *authority => Ok(()),
+        _ => Err(VortexError::Unauthorized.into()),
+    }
+}
+
+// Entry point
+entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // The config must live under the one id every other program checks
+    if *program_id != config_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let instruction = ConfigInstruction::try_from_slice(instruction_data)?;
+
+    match instruction {
+        ConfigInstruction::InitializeConfig { guardian } => {
+            msg!("Instruction: Initialize Config");
+            process_initialize_config(program_id, accounts, guardian)
+        }
+        ConfigInstruction::SetPaused { module, paused } => {
+            msg!("Instruction: Set Paused");
+            process_set_paused(accounts, module, paused)
+        }
+        ConfigInstruction::GrantRole { role, member } => {
+            msg!("Instruction: Grant Role");
+            roles::process_grant_role(program_id, accounts, role, member)
+        }
+        ConfigInstruction::RevokeRole { role, member } => {
+            msg!("Instruction: Revoke Role");
+            roles::process_revoke_role(program_id, accounts, role, member)
+        }
+        ConfigInstruction::RenounceRole { role } => {
+            msg!("Instruction: Renounce Role");
+            roles::process_renounce_role(program_id, accounts, role)
+        }
+    }
+}
+
+fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    guardian: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let program_data = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Only whoever deployed the config program can claim it
+    check_upgrade_authority(program_id, program_data, admin.key)?;
+
+    let (config_key, config_bump) = find_config_address();
+    if *config_account.key != config_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !config_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        admin,
+        config_account,
+        system_program,
+        program_id,
+        &rent,
+        ProgramConfig::LEN,
+        &[CONFIG_SEED, &[config_bump]],
+    )?;
+
+    let config = ProgramConfig {
+        admin: *admin.key,
+        guardian,
+        paused: PauseFlags::default(),
+        bump: config_bump,
+    };
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_paused(
+    accounts: &[AccountInfo],
+    module: PausableModule,
+    paused: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(config_account)?;
+
+    // The guardian can pull the brake but only the admin can release it
+    let is_admin = config.admin == *authority.key;
+    let is_guardian = config.guardian == *authority.key;
+    if !(is_admin || (is_guardian && paused)) {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    config.paused.set(module, paused);
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+
+    emit(VortexEvent::PauseChanged {
+        authority: *authority.key,
+        module,
+        paused,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::system_program;
+    use crate::test_utils::{self, empty, initialized_config, program, program_data, rent_sysvar, run, wallet};
+
+    #[test]
+    fn test_pause_flags_are_independent() {
+        let mut flags = PauseFlags::default();
+        flags.set(PausableModule::Purchase, true);
+
+        assert!(flags.is_paused(PausableModule::Purchase));
+        assert!(!flags.is_paused(PausableModule::Listing));
+        assert!(!flags.is_paused(PausableModule::Token));
+        assert!(!flags.is_paused(PausableModule::Voting));
+
+        flags.set(PausableModule::Purchase, false);
+        assert!(!flags.is_paused(PausableModule::Purchase));
+    }
+
+    #[test]
+    fn test_config_len() {
+        let config = ProgramConfig {
+            admin: Pubkey::new_unique(),
+            guardian: Pubkey::new_unique(),
+            paused: PauseFlags::default(),
+            bump: 255,
+        };
+        assert_eq!(config.try_to_vec().unwrap().len(), ProgramConfig::LEN);
+    }
+
+    #[test]
+    fn test_guardian_pauses_through_dispatcher() {
+        test_utils::install();
+        let admin = wallet();
+        let guardian = wallet();
+        let config_account = initialized_config(&admin, guardian.key);
+        assert_eq!(load_config(&config_account).unwrap().admin, *admin.key);
+
+        let set_paused = |authority: &AccountInfo<'static>, paused: bool| {
+            let data = ConfigInstruction::SetPaused { module: PausableModule::Purchase, paused }
+                .try_to_vec()
+                .unwrap();
+            run(process_instruction, &config_program::id(), &[authority.clone(), config_account.clone()], &data)
+        };
+
+        set_paused(&guardian, true).unwrap();
+        assert_eq!(
+            assert_not_paused(&config_account, PausableModule::Purchase).unwrap_err(),
+            VortexError::ProgramPaused.into()
+        );
+        assert!(assert_not_paused(&config_account, PausableModule::Listing).is_ok());
+
+        // Only the admin can release the brake
+        assert_eq!(set_paused(&guardian, false).unwrap_err(), VortexError::Unauthorized.into());
+        set_paused(&admin, false).unwrap();
+        assert!(assert_not_paused(&config_account, PausableModule::Purchase).is_ok());
+    }
+
+    #[test]
+    fn test_only_upgrade_authority_initializes_config() {
+        test_utils::install();
+        let squatter = wallet();
+        let accounts = [
+            squatter.clone(),
+            empty(find_config_address().0),
+            program_data(&config_program::id(), &Pubkey::new_unique()),
+            program(system_program::id()),
+            rent_sysvar(),
+        ];
+        let data = ConfigInstruction::InitializeConfig { guardian: *squatter.key }.try_to_vec().unwrap();
+        assert_eq!(
+            run(process_instruction, &config_program::id(), &accounts, &data).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+
+        // Nor can the config be created under another program id
+        assert_eq!(
+            run(process_instruction, &Pubkey::new_unique(), &accounts, &data).unwrap_err(),
+            ProgramError::IncorrectProgramId
+        );
+    }
+}
@@ -3,6 +3,10 @@ use solana_program::{
     msg,
     pubkey::Pubkey,
 };
+use crate::config::PausableModule;
+use crate::roles::Role;
+use crate::token::BurnReason;
+use crate::token::genesis::GenesisBucket;
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum VortexEvent {
@@ -17,11 +21,41 @@ pub enum VortexEvent {
         start_time: i64,
         duration: i64,
     },
+    VestingRevoked {
+        beneficiary: Pubkey,
+        vested_amount: u64,
+        clawback_amount: u64,
+        treasury: Pubkey,
+    },
     StakeCreated {
         staker: Pubkey,
         amount: u64,
         duration: i64,
     },
+    Unstaked {
+        staker: Pubkey,
+        amount: u64,
+    },
+    RewardsClaimed {
+        staker: Pubkey,
+        amount: u64,
+    },
+    TokensBurned {
+        burner: Pubkey,
+        amount: u64,
+        reason: BurnReason,
+        total_burned: u64,
+    },
+    MintAuthorityChanged {
+        previous_authority: Pubkey,
+        new_authority: Option<Pubkey>,
+    },
+    GenesisVaultWithdrawn {
+        bucket: GenesisBucket,
+        authority: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+    },
     
     // Marketplace Events
     ArtworkListed {
@@ -35,11 +69,140 @@ pub enum VortexEvent {
         seller: Pubkey,
         nft_mint: Pubkey,
         price: u64,
+        creator_fee: u64,
+        artist_royalty: u64,
+        treasury_fee: u64,
+        seller_proceeds: u64,
     },
     ListingCancelled {
         seller: Pubkey,
         nft_mint: Pubkey,
     },
+    ListingClosed {
+        seller: Pubkey,
+        nft_mint: Pubkey,
+        closed_by: Pubkey,
+    },
+    ListingUpdated {
+        seller: Pubkey,
+        nft_mint: Pubkey,
+        price: u64,
+        royalty_percentage: u8,
+        expires_at: Option<i64>,
+        nonce: u64,
+    },
+    DailyArtMinted {
+        date: u32,
+        nft_mint: Pubkey,
+        artist_count: u8,
+    },
+    ParticipatingArtistAdded {
+        date: u32,
+        artist: Pubkey,
+    },
+    AuctionCreated {
+        seller: Pubkey,
+        nft_mint: Pubkey,
+        reserve: u64,
+        end_time: i64,
+    },
+    BidPlaced {
+        bidder: Pubkey,
+        nft_mint: Pubkey,
+        amount: u64,
+        end_time: i64,
+    },
+    AuctionSettled {
+        nft_mint: Pubkey,
+        winner: Option<Pubkey>,
+        price: u64,
+    },
+    DutchAuctionCreated {
+        seller: Pubkey,
+        nft_mint: Pubkey,
+        start_price: u64,
+        end_price: u64,
+        start_time: i64,
+        end_time: i64,
+    },
+    OfferMade {
+        buyer: Pubkey,
+        nft_mint: Pubkey,
+        amount: u64,
+        expiry: i64,
+    },
+    OfferAccepted {
+        buyer: Pubkey,
+        seller: Pubkey,
+        nft_mint: Pubkey,
+        amount: u64,
+    },
+    OfferCancelled {
+        buyer: Pubkey,
+        nft_mint: Pubkey,
+        amount: u64,
+    },
+    CollectionOfferMade {
+        buyer: Pubkey,
+        collection: Pubkey,
+        price: u64,
+        quantity: u32,
+        expiry: i64,
+    },
+    CollectionOfferFilled {
+        buyer: Pubkey,
+        seller: Pubkey,
+        collection: Pubkey,
+        nft_mint: Pubkey,
+        remaining: u32,
+    },
+    CollectionOfferCancelled {
+        buyer: Pubkey,
+        collection: Pubkey,
+        refund: u64,
+    },
+    BundleListed {
+        seller: Pubkey,
+        bundle: Pubkey,
+        price: u64,
+        piece_count: u8,
+    },
+    BundlePurchased {
+        buyer: Pubkey,
+        seller: Pubkey,
+        bundle: Pubkey,
+        price: u64,
+    },
+    BundleCancelled {
+        seller: Pubkey,
+        bundle: Pubkey,
+    },
+    EditionCreated {
+        artist: Pubkey,
+        master_mint: Pubkey,
+        price: u64,
+        max_supply: u32,
+    },
+    EditionPrintMinted {
+        buyer: Pubkey,
+        master_mint: Pubkey,
+        nft_mint: Pubkey,
+        number: u32,
+        max_supply: u32,
+    },
+    DropCreated {
+        creator: Pubkey,
+        drop: Pubkey,
+        max_supply: u32,
+        phase_count: u8,
+    },
+    DropMinted {
+        buyer: Pubkey,
+        drop: Pubkey,
+        nft_mint: Pubkey,
+        phase: u8,
+        minted: u32,
+    },
     
     // Governance Events
     ProposalCreated {
@@ -59,6 +222,30 @@ pub enum VortexEvent {
         yes_votes: u64,
         no_votes: u64,
     },
+
+    // Admin Events
+    AcceptedMintAdded {
+        mint: Pubkey,
+        treasury: Pubkey,
+    },
+    AcceptedMintRemoved {
+        mint: Pubkey,
+    },
+    PauseChanged {
+        authority: Pubkey,
+        module: PausableModule,
+        paused: bool,
+    },
+    RoleGranted {
+        role: Role,
+        member: Pubkey,
+        admin: Pubkey,
+    },
+    RoleRevoked {
+        role: Role,
+        member: Pubkey,
+        revoked_by: Pubkey,
+    },
 }
 
 impl VortexEvent {
@@ -74,18 +261,111 @@ impl VortexEvent {
             VortexEvent::VestingCreated { beneficiary, amount, start_time, duration } => {
                 msg!("Vesting Created: Beneficiary={}, Amount={}", beneficiary, amount);
             }
+            VortexEvent::VestingRevoked { beneficiary, vested_amount, clawback_amount, treasury } => {
+                msg!("Vesting Revoked: Beneficiary={}, Vested={}, Clawback={}, Treasury={}",
+                    beneficiary, vested_amount, clawback_amount, treasury);
+            }
             VortexEvent::StakeCreated { staker, amount, duration } => {
                 msg!("Stake Created: Staker={}, Amount={}", staker, amount);
             }
+            VortexEvent::Unstaked { staker, amount } => {
+                msg!("Unstaked: Staker={}, Amount={}", staker, amount);
+            }
+            VortexEvent::RewardsClaimed { staker, amount } => {
+                msg!("Rewards Claimed: Staker={}, Amount={}", staker, amount);
+            }
+            VortexEvent::TokensBurned { burner, amount, reason, total_burned } => {
+                msg!("Tokens Burned: Burner={}, Amount={}, Reason={:?}, Total={}",
+                    burner, amount, reason, total_burned);
+            }
+            VortexEvent::MintAuthorityChanged { previous_authority, new_authority } => {
+                msg!("Mint Authority Changed: From={}, To={:?}", previous_authority, new_authority);
+            }
+            VortexEvent::GenesisVaultWithdrawn { bucket, authority, destination, amount } => {
+                msg!("Genesis Vault Withdrawn: Bucket={:?}, Authority={}, Destination={}, Amount={}",
+                    bucket, authority, destination, amount);
+            }
             VortexEvent::ArtworkListed { seller, nft_mint, price, royalty_percentage } => {
                 msg!("Artwork Listed: Seller={}, NFT={}, Price={}", seller, nft_mint, price);
             }
-            VortexEvent::ArtworkPurchased { buyer, seller, nft_mint, price } => {
+            VortexEvent::ArtworkPurchased {
+                buyer, seller, nft_mint, price, creator_fee, artist_royalty, treasury_fee, seller_proceeds,
+            } => {
                 msg!("Artwork Purchased: Buyer={}, NFT={}, Price={}", buyer, nft_mint, price);
+                msg!("Sale Split: Creator={}, Artist={}, Treasury={}, Seller={}",
+                    creator_fee, artist_royalty, treasury_fee, seller_proceeds);
             }
             VortexEvent::ListingCancelled { seller, nft_mint } => {
                 msg!("Listing Cancelled: Seller={}, NFT={}", seller, nft_mint);
             }
+            VortexEvent::ListingClosed { seller, nft_mint, closed_by } => {
+                msg!("Listing Closed: Seller={}, NFT={}, By={}", seller, nft_mint, closed_by);
+            }
+            VortexEvent::ListingUpdated { seller, nft_mint, price, royalty_percentage, expires_at, nonce } => {
+                msg!("Listing Updated: Seller={}, NFT={}, Price={}, Royalty={}%, Expires={:?}, Nonce={}",
+                    seller, nft_mint, price, royalty_percentage, expires_at, nonce);
+            }
+            VortexEvent::DailyArtMinted { date, nft_mint, artist_count } => {
+                msg!("Daily Art Minted: Date={}, NFT={}, Artists={}", date, nft_mint, artist_count);
+            }
+            VortexEvent::ParticipatingArtistAdded { date, artist } => {
+                msg!("Participating Artist Added: Date={}, Artist={}", date, artist);
+            }
+            VortexEvent::AuctionCreated { seller, nft_mint, reserve, end_time } => {
+                msg!("Auction Created: Seller={}, NFT={}, Reserve={}, Ends={}", seller, nft_mint, reserve, end_time);
+            }
+            VortexEvent::BidPlaced { bidder, nft_mint, amount, end_time } => {
+                msg!("Bid Placed: Bidder={}, NFT={}, Amount={}, Ends={}", bidder, nft_mint, amount, end_time);
+            }
+            VortexEvent::AuctionSettled { nft_mint, winner, price } => {
+                msg!("Auction Settled: NFT={}, Winner={:?}, Price={}", nft_mint, winner, price);
+            }
+            VortexEvent::DutchAuctionCreated { seller, nft_mint, start_price, end_price, start_time, end_time } => {
+                msg!("Dutch Auction Created: Seller={}, NFT={}, Price={}->{}, From={}, To={}",
+                    seller, nft_mint, start_price, end_price, start_time, end_time);
+            }
+            VortexEvent::OfferMade { buyer, nft_mint, amount, expiry } => {
+                msg!("Offer Made: Buyer={}, NFT={}, Amount={}, Expiry={}", buyer, nft_mint, amount, expiry);
+            }
+            VortexEvent::OfferAccepted { buyer, seller, nft_mint, amount } => {
+                msg!("Offer Accepted: Buyer={}, Seller={}, NFT={}, Amount={}", buyer, seller, nft_mint, amount);
+            }
+            VortexEvent::OfferCancelled { buyer, nft_mint, amount } => {
+                msg!("Offer Cancelled: Buyer={}, NFT={}, Refund={}", buyer, nft_mint, amount);
+            }
+            VortexEvent::CollectionOfferMade { buyer, collection, price, quantity, expiry } => {
+                msg!("Collection Offer Made: Buyer={}, Collection={}, Price={}, Quantity={}, Expiry={}",
+                    buyer, collection, price, quantity, expiry);
+            }
+            VortexEvent::CollectionOfferFilled { buyer, seller, collection, nft_mint, remaining } => {
+                msg!("Collection Offer Filled: Buyer={}, Seller={}, Collection={}, NFT={}, Remaining={}",
+                    buyer, seller, collection, nft_mint, remaining);
+            }
+            VortexEvent::CollectionOfferCancelled { buyer, collection, refund } => {
+                msg!("Collection Offer Cancelled: Buyer={}, Collection={}, Refund={}", buyer, collection, refund);
+            }
+            VortexEvent::BundleListed { seller, bundle, price, piece_count } => {
+                msg!("Bundle Listed: Seller={}, Bundle={}, Price={}, Pieces={}", seller, bundle, price, piece_count);
+            }
+            VortexEvent::BundlePurchased { buyer, seller, bundle, price } => {
+                msg!("Bundle Purchased: Buyer={}, Seller={}, Bundle={}, Price={}", buyer, seller, bundle, price);
+            }
+            VortexEvent::BundleCancelled { seller, bundle } => {
+                msg!("Bundle Cancelled: Seller={}, Bundle={}", seller, bundle);
+            }
+            VortexEvent::EditionCreated { artist, master_mint, price, max_supply } => {
+                msg!("Edition Created: Artist={}, Master={}, Price={}, Supply={}", artist, master_mint, price, max_supply);
+            }
+            VortexEvent::EditionPrintMinted { buyer, master_mint, nft_mint, number, max_supply } => {
+                msg!("Edition Print Minted: Buyer={}, Master={}, NFT={}, Print={}/{}",
+                    buyer, master_mint, nft_mint, number, max_supply);
+            }
+            VortexEvent::DropCreated { creator, drop, max_supply, phase_count } => {
+                msg!("Drop Created: Creator={}, Drop={}, Supply={}, Phases={}", creator, drop, max_supply, phase_count);
+            }
+            VortexEvent::DropMinted { buyer, drop, nft_mint, phase, minted } => {
+                msg!("Drop Minted: Buyer={}, Drop={}, NFT={}, Phase={}, Minted={}", buyer, drop, nft_mint, phase, minted);
+            }
             VortexEvent::ProposalCreated { creator, proposal_id, title, voting_period } => {
                 msg!("Proposal Created: ID={}, Title={}", proposal_id, title);
             }
@@ -97,6 +377,21 @@ impl VortexEvent {
                 msg!("Proposal Executed: ID={}, Yes={}, No={}", 
                     proposal_id, yes_votes, no_votes);
             }
+            VortexEvent::AcceptedMintAdded { mint, treasury } => {
+                msg!("Accepted Mint Added: Mint={}, Treasury={}", mint, treasury);
+            }
+            VortexEvent::AcceptedMintRemoved { mint } => {
+                msg!("Accepted Mint Removed: Mint={}", mint);
+            }
+            VortexEvent::PauseChanged { authority, module, paused } => {
+                msg!("Pause Changed: Module={:?}, Paused={}, By={}", module, paused, authority);
+            }
+            VortexEvent::RoleGranted { role, member, admin } => {
+                msg!("Role Granted: Role={:?}, Member={}, By={}", role, member, admin);
+            }
+            VortexEvent::RoleRevoked { role, member, revoked_by } => {
+                msg!("Role Revoked: Role={:?}, Member={}, By={}", role, member, revoked_by);
+            }
         }
     }
 }
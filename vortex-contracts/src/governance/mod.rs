@@ -5,8 +5,19 @@ use solana_program::{
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::roles::{require_role, Role};
+use crate::token::staking::{effective_weight, StakePosition, STAKE_POOL_SEED};
+use crate::token::{tola_program, TolaState, STATE_SEED};
+use crate::utils::create_pda_account;
+use crate::VortexError;
+
+/// Seed of a vote record, followed by the proposal and the stake position
+pub const VOTE_RECORD_SEED: &[u8] = b"vote_record";
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum GovernanceInstruction {
@@ -15,6 +26,7 @@ pub enum GovernanceInstruction {
     /// Accounts expected:
     /// 0. `[signer]` The proposal creator's account
     /// 1. `[writable]` The proposal account
+    /// 2. `[]` The program config account (PDA of `["config"]`)
     CreateProposal {
         title: String,
         description: String,
@@ -23,10 +35,22 @@ pub enum GovernanceInstruction {
 
     /// Cast a vote on a proposal
     /// 
+    /// `amount` may not exceed the effective weight of the voter's stake
+    /// position in the TOLA stake pool at the time of voting. The position
+    /// must stay locked until voting ends, so its tokens can't be unstaked
+    /// and vote again from another wallet. Each position votes once per
+    /// proposal, which is recorded in a vote record.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` The voter's account
+    /// 0. `[signer, writable]` The voter's account, pays for the vote record
     /// 1. `[writable]` The proposal account
     /// 2. `[writable]` The voter's token account
+    /// 3. `[]` The voter's stake position, owned by the TOLA token program
+    /// 4. `[]` The program config account (PDA of `["config"]`)
+    /// 5. `[]` The TOLA token state account (PDA of `["tola_state"]` under the TOLA token program)
+    /// 6. `[writable]` The vote record (PDA of `["vote_record", proposal, stake_position]`)
+    /// 7. `[]` The system program
+    /// 8. `[]` The rent sysvar
     CastVote {
         vote: bool,
         amount: u64,
@@ -35,8 +59,10 @@ pub enum GovernanceInstruction {
     /// Execute a proposal
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The executor's account
+    /// 0. `[signer]` The executor's account, must hold the DAO role
     /// 1. `[writable]` The proposal account
+    /// 2. `[]` The program config account (PDA of `["config"]`)
+    /// 3. `[]` The executor's role membership (PDA of `["role", role, member]`)
     ExecuteProposal {},
 }
 
@@ -52,6 +78,26 @@ pub struct Proposal {
     pub executed: bool,
 }
 
+/// Marks a stake position as having voted on a proposal
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub stake_position: Pubkey,
+    pub voter: Pubkey,
+    pub vote: bool,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 32 + 32 + 32 + 1 + 8 + 1;
+}
+
+/// Address of the TOLA stake pool, the only pool whose positions can vote
+pub fn find_tola_pool_address(tola_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_POOL_SEED, tola_mint.as_ref()], &tola_program::id())
+}
+
 // Entry point
 entrypoint!(process_instruction);
 
@@ -88,12 +134,20 @@ fn process_create_proposal(
     let account_info_iter = &mut accounts.iter();
     let creator = next_account_info(account_info_iter)?;
     let proposal_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Voting)?;
 
     // Verify creator is signer
     if !creator.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    if proposal_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
     // Create proposal
     let proposal = Proposal {
         creator: *creator.key,
@@ -120,12 +174,54 @@ fn process_cast_vote(
     let voter = next_account_info(account_info_iter)?;
     let proposal_account = next_account_info(account_info_iter)?;
     let voter_token_account = next_account_info(account_info_iter)?;
+    let stake_position_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let vote_record_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Voting)?;
 
     // Verify voter is signer
     if !voter.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    if proposal_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Only positions in the TOLA stake pool carry voting power
+    let (state_key, _) = Pubkey::find_program_address(&[STATE_SEED], &tola_program::id());
+    if *state_account.key != state_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *state_account.owner != tola_program::id() || *stake_position_account.owner != tola_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let state = TolaState::try_from_slice(&state_account.data.borrow())?;
+    let position = StakePosition::try_from_slice(&stake_position_account.data.borrow())?;
+    if position.owner != *voter.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+    if position.pool != find_tola_pool_address(&state.mint).0 {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    // One vote per position and proposal
+    let (record_key, record_bump) = Pubkey::find_program_address(
+        &[VOTE_RECORD_SEED, proposal_account.key.as_ref(), stake_position_account.key.as_ref()],
+        program_id,
+    );
+    if *vote_record_account.key != record_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !vote_record_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
     // Update vote counts
     let mut proposal = Proposal::try_from_slice(&proposal_account.data.borrow())?;
     let current_time = solana_program::clock::Clock::get()?.unix_timestamp;
@@ -134,6 +230,41 @@ fn process_cast_vote(
         return Err(ProgramError::InvalidInstructionData);
     }
 
+    // Stake that unlocks before the end could vote again from another wallet
+    if position.lock_end < proposal.end_time {
+        return Err(VortexError::InvalidState.into());
+    }
+
+    // Voting power is the lockup-weighted stake
+    if amount > effective_weight(&position, current_time) {
+        return Err(VortexError::InsufficientFunds.into());
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        voter,
+        vote_record_account,
+        system_program,
+        program_id,
+        &rent,
+        VoteRecord::LEN,
+        &[
+            VOTE_RECORD_SEED,
+            proposal_account.key.as_ref(),
+            stake_position_account.key.as_ref(),
+            &[record_bump],
+        ],
+    )?;
+    let record = VoteRecord {
+        proposal: *proposal_account.key,
+        stake_position: *stake_position_account.key,
+        voter: *voter.key,
+        vote,
+        amount,
+        bump: record_bump,
+    };
+    record.serialize(&mut *vote_record_account.data.borrow_mut())?;
+
     if vote {
         proposal.yes_votes += amount;
     } else {
@@ -151,10 +282,17 @@ fn process_execute_proposal(
     let account_info_iter = &mut accounts.iter();
     let executor = next_account_info(account_info_iter)?;
     let proposal_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let dao_membership = next_account_info(account_info_iter)?;
 
-    // Verify executor is signer
-    if !executor.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Voting)?;
+
+    // Only the DAO may execute proposals
+    require_role(executor, dao_membership, Role::Dao)?;
+
+    if proposal_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
     }
 
     // Execute proposal
@@ -178,4 +316,183 @@ fn process_execute_proposal(
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::system_program;
+    use crate::config::find_config_address;
+    use crate::token::BurnBreakdown;
+    use crate::test_utils::{self, account, empty, program, rent_sysvar, run, wallet};
+
+    fn tola_state(mint: Pubkey) -> AccountInfo<'static> {
+        let state = TolaState {
+            total_supply: 1_000,
+            mint_authority: Pubkey::new_unique(),
+            initialized: true,
+            mint,
+            supply_account: Pubkey::new_unique(),
+            bump: 255,
+            total_burned: 0,
+            burned_by_reason: BurnBreakdown::default(),
+            genesis_distributed: true,
+            mint_authority_revoked: false,
+        };
+        let key = Pubkey::find_program_address(&[STATE_SEED], &tola_program::id()).0;
+        account(key, tola_program::id(), 1, state.try_to_vec().unwrap(), false)
+    }
+
+    fn stake_position(owner: &Pubkey, pool: Pubkey, program: Pubkey, lock_end: i64) -> AccountInfo<'static> {
+        let position = StakePosition {
+            owner: *owner,
+            pool,
+            amount: 100,
+            lock_duration: lock_end - test_utils::NOW,
+            lock_end,
+            multiplier_bps: 10_000,
+            weight: 100,
+            reward_per_token_paid: 0,
+            pending_rewards: 0,
+            bump: 255,
+        };
+        account(Pubkey::new_unique(), program, 1, position.try_to_vec().unwrap(), false)
+    }
+
+    #[test]
+    fn test_vote_record_len() {
+        let record = VoteRecord {
+            proposal: Pubkey::new_unique(),
+            stake_position: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+            vote: true,
+            amount: 1,
+            bump: 255,
+        };
+        assert_eq!(record.try_to_vec().unwrap().len(), VoteRecord::LEN);
+    }
+
+    #[test]
+    fn test_position_votes_once_from_the_tola_pool() {
+        test_utils::install();
+        let program_id = Pubkey::new_unique();
+        let voter = wallet();
+        let tola_mint = Pubkey::new_unique();
+        let state_account = tola_state(tola_mint);
+        let proposal = Proposal {
+            creator: Pubkey::new_unique(),
+            title: "Fund".to_string(),
+            description: "Fund the pool".to_string(),
+            start_time: test_utils::NOW,
+            end_time: test_utils::NOW + 100,
+            yes_votes: 0,
+            no_votes: 0,
+            executed: false,
+        };
+        let proposal_account = account(Pubkey::new_unique(), program_id, 1, proposal.try_to_vec().unwrap(), false);
+
+        let vote_record = |position: &AccountInfo<'static>| {
+            empty(
+                Pubkey::find_program_address(
+                    &[VOTE_RECORD_SEED, proposal_account.key.as_ref(), position.key.as_ref()],
+                    &program_id,
+                )
+                .0,
+            )
+        };
+        let cast_vote = |position: &AccountInfo<'static>, vote_record: &AccountInfo<'static>| {
+            let accounts = [
+                voter.clone(),
+                proposal_account.clone(),
+                empty(Pubkey::new_unique()),
+                position.clone(),
+                empty(find_config_address().0),
+                state_account.clone(),
+                vote_record.clone(),
+                program(system_program::id()),
+                rent_sysvar(),
+            ];
+            let data = GovernanceInstruction::CastVote { vote: true, amount: 100 }.try_to_vec().unwrap();
+            run(process_instruction, &program_id, &accounts, &data)
+        };
+
+        // Positions of other programs or pools carry no voting power
+        let tola_pool = find_tola_pool_address(&tola_mint).0;
+        let forged = stake_position(voter.key, tola_pool, program_id, test_utils::NOW + 100);
+        assert_eq!(cast_vote(&forged, &vote_record(&forged)).unwrap_err(), ProgramError::IncorrectProgramId);
+        let other_pool = stake_position(voter.key, Pubkey::new_unique(), tola_program::id(), test_utils::NOW + 100);
+        assert_eq!(
+            cast_vote(&other_pool, &vote_record(&other_pool)).unwrap_err(),
+            VortexError::InvalidAccount.into()
+        );
+
+        let position = stake_position(voter.key, tola_pool, tola_program::id(), test_utils::NOW + 100);
+        let record_account = vote_record(&position);
+        cast_vote(&position, &record_account).unwrap();
+        assert_eq!(
+            cast_vote(&position, &record_account).unwrap_err(),
+            ProgramError::AccountAlreadyInitialized
+        );
+        let proposal = Proposal::try_from_slice(&proposal_account.data.borrow()).unwrap();
+        assert_eq!(proposal.yes_votes, 100);
+    }
+
+    #[test]
+    fn test_recycled_stake_cannot_vote_twice() {
+        test_utils::install();
+        let program_id = Pubkey::new_unique();
+        let tola_mint = Pubkey::new_unique();
+        let tola_pool = find_tola_pool_address(&tola_mint).0;
+        let state_account = tola_state(tola_mint);
+        let proposal = Proposal {
+            creator: Pubkey::new_unique(),
+            title: "Fund".to_string(),
+            description: "Fund the pool".to_string(),
+            start_time: test_utils::NOW,
+            end_time: test_utils::NOW + 100,
+            yes_votes: 0,
+            no_votes: 0,
+            executed: false,
+        };
+        let proposal_account = account(Pubkey::new_unique(), program_id, 1, proposal.try_to_vec().unwrap(), false);
+
+        let cast_vote = |voter: &AccountInfo<'static>, position: &AccountInfo<'static>| {
+            let record_key = Pubkey::find_program_address(
+                &[VOTE_RECORD_SEED, proposal_account.key.as_ref(), position.key.as_ref()],
+                &program_id,
+            )
+            .0;
+            let accounts = [
+                voter.clone(),
+                proposal_account.clone(),
+                empty(Pubkey::new_unique()),
+                position.clone(),
+                empty(find_config_address().0),
+                state_account.clone(),
+                empty(record_key),
+                program(system_program::id()),
+                rent_sysvar(),
+            ];
+            let data = GovernanceInstruction::CastVote { vote: true, amount: 100 }.try_to_vec().unwrap();
+            run(process_instruction, &program_id, &accounts, &data)
+        };
+
+        // A position that unlocks mid-vote could be unstaked, moved to a
+        // second wallet and staked to vote again
+        let first = wallet();
+        let short_lock = stake_position(first.key, tola_pool, tola_program::id(), test_utils::NOW + 99);
+        assert_eq!(cast_vote(&first, &short_lock).unwrap_err(), VortexError::InvalidState.into());
+
+        let locked = stake_position(first.key, tola_pool, tola_program::id(), test_utils::NOW + 100);
+        cast_vote(&first, &locked).unwrap();
+
+        // Tokens freed before the end and restaked from a second wallet
+        // are locked for less than the vote, so they can't take part
+        let second = wallet();
+        let recycled = stake_position(second.key, tola_pool, tola_program::id(), test_utils::NOW + 1);
+        assert_eq!(cast_vote(&second, &recycled).unwrap_err(), VortexError::InvalidState.into());
+
+        let proposal = Proposal::try_from_slice(&proposal_account.data.borrow()).unwrap();
+        assert_eq!(proposal.yes_votes, 100);
+    }
+}
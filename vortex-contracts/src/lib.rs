@@ -1,7 +1,13 @@
 pub mod token;
 pub mod marketplace;
 pub mod governance;
+pub mod config;
+pub mod roles;
 pub mod events;
+pub mod utils;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 #[cfg(test)]
 mod tests;
@@ -10,6 +16,7 @@ mod tests;
 pub use token::process_instruction as process_token_instruction;
 pub use marketplace::process_instruction as process_marketplace_instruction;
 pub use governance::process_instruction as process_governance_instruction;
+pub use config::process_instruction as process_config_instruction;
 pub use events::{VortexEvent, emit};
 
 // Error types
@@ -20,6 +27,23 @@ pub enum VortexError {
     InsufficientFunds,
     InvalidState,
     Unauthorized,
+    ProgramPaused,
+    ListingInactive,
+    PriceExceedsMaximum,
+    ListingMismatch,
+    StaleListing,
+    BidTooLow,
+    AuctionEnded,
+    AuctionInProgress,
+    OfferExpired,
+    NotInCollection,
+    ListingExpired,
+    RoyaltyLocked,
+    CurrencyNotAccepted,
+    EditionSoldOut,
+    DropSoldOut,
+    NotAllowlisted,
+    MintCapReached,
 }
 
 impl From<VortexError> for solana_program::program_error::ProgramError {
@@ -0,0 +1,454 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token::state::Account as TokenAccount;
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::settlement::{check_token_account, load_marketplace_config, settle_sale, SaleAccounts};
+use crate::marketplace::{load_listing, open_listing, ListingAccounts, ListingMode};
+use crate::utils::{close_token_account, create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+/// Seed of an auction's bid vault, followed by the listing
+pub const BID_VAULT_SEED: &[u8] = b"bid_vault";
+/// Longest an auction may run from its creation, in seconds
+pub const MAX_AUCTION_DURATION: i64 = 30 * 24 * 60 * 60;
+/// Longest anti-sniping extension window, in seconds
+pub const MAX_EXTENSION_WINDOW: i64 = 24 * 60 * 60;
+
+/// State of an English auction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Auction {
+    /// Lowest acceptable first bid
+    pub reserve: u64,
+    /// Each bid must beat the previous one by this much
+    pub min_increment_bps: u16,
+    pub end_time: i64,
+    /// A bid within this many seconds of the end pushes the end back to
+    /// this many seconds from the bid
+    pub extension_window: i64,
+    pub highest_bid: u64,
+    /// `Pubkey::default()` until the first bid
+    pub highest_bidder: Pubkey,
+    pub bid_count: u32,
+    /// Token account holding the highest bid, owned by the listing
+    pub bid_vault: Pubkey,
+}
+
+impl Auction {
+    pub const LEN: usize = 8 + 2 + 8 + 8 + 8 + 32 + 4 + 32;
+
+    /// Smallest amount the next bid may offer, never zero so that an empty
+    /// bid can't lock the seller out of cancelling
+    pub fn min_next_bid(&self) -> u64 {
+        if self.bid_count == 0 {
+            return self.reserve.max(1);
+        }
+        let increment = (self.highest_bid as u128 * self.min_increment_bps as u128 / 10_000) as u64;
+        self.highest_bid.saturating_add(increment.max(1))
+    }
+
+    /// Record a bid at `now`, extending the auction if it lands in the
+    /// extension window
+    pub fn place_bid(&mut self, bidder: Pubkey, amount: u64, now: i64) -> ProgramResult {
+        if now >= self.end_time {
+            return Err(VortexError::AuctionEnded.into());
+        }
+        if amount < self.min_next_bid() {
+            return Err(VortexError::BidTooLow.into());
+        }
+
+        self.highest_bid = amount;
+        self.highest_bidder = bidder;
+        self.bid_count += 1;
+
+        // Anti-sniping
+        if self.end_time - now < self.extension_window {
+            self.end_time = now
+                .checked_add(self.extension_window)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Auction terms are sane at `now`; the caps keep every end time reachable
+// through extensions from overflowing
+pub(crate) fn is_valid_auction(min_increment_bps: u16, end_time: i64, extension_window: i64, now: i64) -> bool {
+    end_time > now
+        && end_time - now <= MAX_AUCTION_DURATION
+        && (0..=MAX_EXTENSION_WINDOW).contains(&extension_window)
+        && min_increment_bps <= 10_000
+}
+
+// Escrow the NFT and open an auction for it
+pub fn process_create_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reserve: u64,
+    min_increment_bps: u16,
+    end_time: i64,
+    extension_window: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let listing_accounts = ListingAccounts::next(account_info_iter)?;
+    let bid_vault = next_account_info(account_info_iter)?;
+    let payment_mint = next_account_info(account_info_iter)?;
+    let marketplace_config = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    if !is_valid_auction(min_increment_bps, end_time, extension_window, now) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let config = load_marketplace_config(program_id, marketplace_config)?;
+    if *payment_mint.key != config.payment_mint {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    let listing_key = *listing_accounts.listing_account.key;
+    let (bid_vault_key, bid_vault_bump) =
+        Pubkey::find_program_address(&[BID_VAULT_SEED, listing_key.as_ref()], program_id);
+    if *bid_vault.key != bid_vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let auction = Auction {
+        reserve,
+        min_increment_bps,
+        end_time,
+        extension_window,
+        highest_bid: 0,
+        highest_bidder: Pubkey::default(),
+        bid_count: 0,
+        bid_vault: bid_vault_key,
+    };
+    open_listing(program_id, &listing_accounts, reserve, 0, None, None, ListingMode::Auction(auction))?;
+
+    let rent = Rent::from_account_info(listing_accounts.rent_account)?;
+
+    create_pda_token_account(
+        listing_accounts.seller,
+        bid_vault,
+        payment_mint,
+        &listing_key,
+        listing_accounts.system_program,
+        listing_accounts.token_program,
+        &rent,
+        &[BID_VAULT_SEED, listing_key.as_ref(), &[bid_vault_bump]],
+    )?;
+
+    emit(VortexEvent::AuctionCreated {
+        seller: *listing_accounts.seller.key,
+        nft_mint: *listing_accounts.nft_mint.key,
+        reserve,
+        end_time,
+    });
+
+    Ok(())
+}
+
+// Escrow a higher bid and refund the one it beats
+pub fn process_place_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let bidder = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let bidder_token_account = next_account_info(account_info_iter)?;
+    let bid_vault = next_account_info(account_info_iter)?;
+    let previous_bidder_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify bidder is signer
+    if !bidder.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut listing = load_listing(program_id, listing_account)?;
+    if !listing.is_active {
+        return Err(VortexError::ListingInactive.into());
+    }
+    if listing.seller == *bidder.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+    let ListingMode::Auction(auction) = &mut listing.mode else {
+        return Err(VortexError::InvalidState.into());
+    };
+    if auction.bid_vault != *bid_vault.key {
+        return Err(VortexError::ListingMismatch.into());
+    }
+
+    let previous_bidder = auction.highest_bidder;
+    let previous_bid = auction.highest_bid;
+    let had_bid = auction.bid_count > 0;
+    auction.place_bid(*bidder.key, amount, Clock::get()?.unix_timestamp)?;
+    let end_time = auction.end_time;
+
+    transfer_tokens(token_program, bidder_token_account, bid_vault, bidder, amount, &[])?;
+
+    // Refund the bid that was beaten
+    if had_bid {
+        let vault = TokenAccount::unpack(&bid_vault.data.borrow())?;
+        check_token_account(previous_bidder_token_account, &previous_bidder, &vault.mint)?;
+        let bump = [listing.bump];
+        let listing_seeds = listing.signer_seeds(&bump);
+        transfer_tokens(
+            token_program,
+            bid_vault,
+            previous_bidder_token_account,
+            listing_account,
+            previous_bid,
+            &[&listing_seeds],
+        )?;
+    }
+
+    listing.serialize(&mut *listing_account.data.borrow_mut())?;
+
+    emit(VortexEvent::BidPlaced {
+        bidder: *bidder.key,
+        nft_mint: listing.nft_mint,
+        amount,
+        end_time,
+    });
+
+    Ok(())
+}
+
+// Close an ended auction: pay out and deliver the NFT, or return it unsold
+pub fn process_settle_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let caller = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let bid_vault = next_account_info(account_info_iter)?;
+    let recipient_nft_account = next_account_info(account_info_iter)?;
+    let seller = next_account_info(account_info_iter)?;
+    let sale = SaleAccounts::next(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify caller is signer
+    if !caller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut listing = load_listing(program_id, listing_account)?;
+    if !listing.is_active {
+        return Err(VortexError::ListingInactive.into());
+    }
+    if listing.seller != *seller.key || listing.escrow != *escrow_account.key {
+        return Err(VortexError::ListingMismatch.into());
+    }
+    let ListingMode::Auction(auction) = listing.mode.clone() else {
+        return Err(VortexError::InvalidState.into());
+    };
+    if auction.bid_vault != *bid_vault.key {
+        return Err(VortexError::ListingMismatch.into());
+    }
+    if Clock::get()?.unix_timestamp < auction.end_time {
+        return Err(VortexError::AuctionInProgress.into());
+    }
+
+    // The NFT goes to the winner, or back to the seller without bids
+    let winner = if auction.bid_count > 0 {
+        Some(auction.highest_bidder)
+    } else {
+        None
+    };
+    let recipient = TokenAccount::unpack(&recipient_nft_account.data.borrow())?;
+    if recipient.owner != winner.unwrap_or(listing.seller) {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    let bump = [listing.bump];
+    let listing_seeds = listing.signer_seeds(&bump);
+
+    if let Some(winner) = winner {
+        let breakdown = settle_sale(
+            program_id,
+            &sale,
+            account_info_iter,
+            &listing.nft_mint,
+            &listing.seller,
+            auction.highest_bid,
+            None,
+            bid_vault,
+            listing_account,
+            token_program,
+            &[&listing_seeds],
+        )?;
+
+        emit(VortexEvent::ArtworkPurchased {
+            buyer: winner,
+            seller: listing.seller,
+            nft_mint: listing.nft_mint,
+            price: auction.highest_bid,
+            creator_fee: breakdown.creator_fee,
+            artist_royalty: breakdown.artist_royalty,
+            treasury_fee: breakdown.treasury_fee,
+            seller_proceeds: breakdown.seller_proceeds,
+        });
+    }
+
+    transfer_tokens(
+        token_program,
+        escrow_account,
+        recipient_nft_account,
+        listing_account,
+        1,
+        &[&listing_seeds],
+    )?;
+    close_token_account(token_program, escrow_account, seller, listing_account, &[&listing_seeds])?;
+    close_token_account(token_program, bid_vault, seller, listing_account, &[&listing_seeds])?;
+
+    listing.is_active = false;
+    listing.nonce = listing.nonce.wrapping_add(1);
+    listing.serialize(&mut *listing_account.data.borrow_mut())?;
+
+    emit(VortexEvent::AuctionSettled {
+        nft_mint: listing.nft_mint,
+        winner,
+        price: auction.highest_bid,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction() -> Auction {
+        Auction {
+            reserve: 1_000,
+            min_increment_bps: 500,
+            end_time: 10_000,
+            extension_window: 600,
+            highest_bid: 0,
+            highest_bidder: Pubkey::default(),
+            bid_count: 0,
+            bid_vault: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_first_bid_must_meet_reserve() {
+        let mut auction = auction();
+        assert_eq!(
+            auction.place_bid(Pubkey::new_unique(), 999, 0),
+            Err(VortexError::BidTooLow.into())
+        );
+        auction.place_bid(Pubkey::new_unique(), 1_000, 0).unwrap();
+        assert_eq!(auction.highest_bid, 1_000);
+        assert_eq!(auction.bid_count, 1);
+    }
+
+    #[test]
+    fn test_zero_bid_rejected_without_reserve() {
+        let mut auction = auction();
+        auction.reserve = 0;
+        assert_eq!(
+            auction.place_bid(Pubkey::new_unique(), 0, 0),
+            Err(VortexError::BidTooLow.into())
+        );
+        assert_eq!(auction.bid_count, 0);
+        auction.place_bid(Pubkey::new_unique(), 1, 0).unwrap();
+        assert_eq!(auction.bid_count, 1);
+    }
+
+    #[test]
+    fn test_min_increment() {
+        let mut auction = auction();
+        auction.place_bid(Pubkey::new_unique(), 2_000, 0).unwrap();
+        assert_eq!(auction.min_next_bid(), 2_100);
+        assert_eq!(
+            auction.place_bid(Pubkey::new_unique(), 2_099, 0),
+            Err(VortexError::BidTooLow.into())
+        );
+        auction.place_bid(Pubkey::new_unique(), 2_100, 0).unwrap();
+    }
+
+    #[test]
+    fn test_zero_increment_still_needs_a_higher_bid() {
+        let mut auction = auction();
+        auction.min_increment_bps = 0;
+        auction.place_bid(Pubkey::new_unique(), 1_000, 0).unwrap();
+        assert_eq!(auction.min_next_bid(), 1_001);
+    }
+
+    #[test]
+    fn test_late_bid_extends_auction() {
+        let mut auction = auction();
+        auction.place_bid(Pubkey::new_unique(), 1_000, 5_000).unwrap();
+        assert_eq!(auction.end_time, 10_000);
+
+        auction.place_bid(Pubkey::new_unique(), 2_000, 9_900).unwrap();
+        assert_eq!(auction.end_time, 10_500);
+    }
+
+    #[test]
+    fn test_auction_terms_are_capped() {
+        let now = 1_000_000;
+        assert!(is_valid_auction(500, now + MAX_AUCTION_DURATION, MAX_EXTENSION_WINDOW, now));
+        assert!(!is_valid_auction(500, now, 600, now));
+        assert!(!is_valid_auction(500, now + MAX_AUCTION_DURATION + 1, 600, now));
+        assert!(!is_valid_auction(500, i64::MAX, 600, now));
+        assert!(!is_valid_auction(500, now + 3_600, MAX_EXTENSION_WINDOW + 1, now));
+        assert!(!is_valid_auction(500, now + 3_600, -1, now));
+        assert!(!is_valid_auction(10_001, now + 3_600, 600, now));
+    }
+
+    #[test]
+    fn test_extension_overflow_is_an_error() {
+        let mut auction = auction();
+        auction.end_time = i64::MAX;
+        auction.extension_window = 600;
+        assert_eq!(
+            auction.place_bid(Pubkey::new_unique(), 1_000, i64::MAX - 1),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn test_no_bids_after_end() {
+        let mut auction = auction();
+        assert_eq!(
+            auction.place_bid(Pubkey::new_unique(), 5_000, 10_000),
+            Err(VortexError::AuctionEnded.into())
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::provenance::load_or_create_provenance;
+use crate::marketplace::royalty::split_evenly;
+use crate::marketplace::settlement::{check_token_account, settle_sale, SaleAccounts};
+use crate::utils::{close_program_account, close_token_account, create_pda_account, create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+/// Seed of a bundle PDA, followed by the seller and the bundle id
+pub const BUNDLE_SEED: &[u8] = b"bundle";
+/// Seed of a bundled NFT's escrow, followed by the bundle and the NFT mint
+pub const BUNDLE_ESCROW_SEED: &[u8] = b"bundle_escrow";
+
+/// Most NFTs a single bundle can hold
+pub const MAX_BUNDLE_SIZE: usize = 8;
+
+/// Several NFTs sold together for a single price
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Bundle {
+    pub seller: Pubkey,
+    /// Chosen by the seller to tell their bundles apart
+    pub bundle_id: u64,
+    pub price: u64,
+    /// Escrowed NFTs, in the order their accounts are passed
+    pub nft_mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl Bundle {
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 32 * MAX_BUNDLE_SIZE + 1;
+
+    /// Part of the price paid out for each piece, through its own provenance
+    pub fn piece_prices(&self) -> Vec<u64> {
+        split_evenly(self.price, self.nft_mints.len())
+    }
+
+    /// Signer seeds of the bundle PDA, which owns the escrows
+    pub fn signer_seeds<'a>(&'a self, bundle_id: &'a [u8], bump: &'a [u8]) -> [&'a [u8]; 4] {
+        [BUNDLE_SEED, self.seller.as_ref(), bundle_id, bump]
+    }
+}
+
+/// Address of `seller`'s bundle `bundle_id`
+pub fn find_bundle_address(program_id: &Pubkey, seller: &Pubkey, bundle_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUNDLE_SEED, seller.as_ref(), &bundle_id.to_le_bytes()], program_id)
+}
+
+// Load a bundle PDA owned by this program
+fn load_bundle(program_id: &Pubkey, bundle_account: &AccountInfo) -> Result<Bundle, ProgramError> {
+    if bundle_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(Bundle::deserialize(&mut &bundle_account.data.borrow()[..])?)
+}
+
+// Escrow every piece under a new bundle
+pub fn process_list_bundle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bundle_id: u64,
+    price: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let seller = next_account_info(account_info_iter)?;
+    let bundle_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify seller is signer
+    if !seller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Each piece is passed as its mint, the seller's NFT account, its escrow,
+    // its provenance and its metadata
+    let pieces = account_info_iter.as_slice().chunks_exact(5);
+    if !pieces.remainder().is_empty() || !(2..=MAX_BUNDLE_SIZE).contains(&pieces.len()) {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let (bundle_key, bundle_bump) = find_bundle_address(program_id, seller.key, bundle_id);
+    if *bundle_account.key != bundle_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !bundle_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        seller,
+        bundle_account,
+        system_program,
+        program_id,
+        &rent,
+        Bundle::LEN,
+        &[BUNDLE_SEED, seller.key.as_ref(), &bundle_id.to_le_bytes(), &[bundle_bump]],
+    )?;
+
+    let mut nft_mints = Vec::new();
+    for piece in pieces {
+        let (nft_mint, seller_nft_account, escrow_account, provenance_account, metadata_account) =
+            (&piece[0], &piece[1], &piece[2], &piece[3], &piece[4]);
+        if nft_mints.contains(nft_mint.key) {
+            return Err(VortexError::InvalidAccount.into());
+        }
+        check_token_account(seller_nft_account, seller.key, nft_mint.key)?;
+
+        let (escrow_key, escrow_bump) = Pubkey::find_program_address(
+            &[BUNDLE_ESCROW_SEED, bundle_key.as_ref(), nft_mint.key.as_ref()],
+            program_id,
+        );
+        if *escrow_account.key != escrow_key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        load_or_create_provenance(
+            program_id,
+            provenance_account,
+            metadata_account,
+            nft_mint.key,
+            seller,
+            system_program,
+            &rent,
+        )?;
+
+        create_pda_token_account(
+            seller,
+            escrow_account,
+            nft_mint,
+            &bundle_key,
+            system_program,
+            token_program,
+            &rent,
+            &[BUNDLE_ESCROW_SEED, bundle_key.as_ref(), nft_mint.key.as_ref(), &[escrow_bump]],
+        )?;
+        transfer_tokens(token_program, seller_nft_account, escrow_account, seller, 1, &[])?;
+
+        nft_mints.push(*nft_mint.key);
+    }
+
+    let bundle = Bundle {
+        seller: *seller.key,
+        bundle_id,
+        price,
+        nft_mints,
+        bump: bundle_bump,
+    };
+    bundle.serialize(&mut *bundle_account.data.borrow_mut())?;
+
+    emit(VortexEvent::BundleListed {
+        seller: *seller.key,
+        bundle: bundle_key,
+        price,
+        piece_count: bundle.nft_mints.len() as u8,
+    });
+
+    Ok(())
+}
+
+// Buy every piece of a bundle, paying each out through its own provenance
+pub fn process_purchase_bundle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_price: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let buyer = next_account_info(account_info_iter)?;
+    let bundle_account = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let seller = next_account_info(account_info_iter)?;
+    let seller_token_account = next_account_info(account_info_iter)?;
+    let creator_token_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let marketplace_config = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify buyer is signer
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let bundle = load_bundle(program_id, bundle_account)?;
+    if bundle.seller != *seller.key {
+        return Err(VortexError::ListingMismatch.into());
+    }
+    if bundle.price > max_price {
+        return Err(VortexError::PriceExceedsMaximum.into());
+    }
+
+    let bundle_id = bundle.bundle_id.to_le_bytes();
+    let bump = [bundle.bump];
+    let bundle_seeds = bundle.signer_seeds(&bundle_id, &bump);
+
+    for (nft_mint, piece_price) in bundle.nft_mints.iter().zip(bundle.piece_prices()) {
+        let escrow_account = next_account_info(account_info_iter)?;
+        let buyer_nft_account = next_account_info(account_info_iter)?;
+        let sale = SaleAccounts {
+            seller_token_account,
+            creator_token_account,
+            treasury_token_account,
+            artist_token_account: next_account_info(account_info_iter)?,
+            provenance_account: next_account_info(account_info_iter)?,
+            marketplace_config,
+        };
+
+        let (escrow_key, _) = Pubkey::find_program_address(
+            &[BUNDLE_ESCROW_SEED, bundle_account.key.as_ref(), nft_mint.as_ref()],
+            program_id,
+        );
+        if *escrow_account.key != escrow_key {
+            return Err(VortexError::ListingMismatch.into());
+        }
+        check_token_account(buyer_nft_account, buyer.key, nft_mint)?;
+
+        let breakdown = settle_sale(
+            program_id,
+            &sale,
+            account_info_iter,
+            nft_mint,
+            &bundle.seller,
+            piece_price,
+            None,
+            buyer_token_account,
+            buyer,
+            token_program,
+            &[],
+        )?;
+
+        transfer_tokens(
+            token_program,
+            escrow_account,
+            buyer_nft_account,
+            bundle_account,
+            1,
+            &[&bundle_seeds],
+        )?;
+        close_token_account(token_program, escrow_account, seller, bundle_account, &[&bundle_seeds])?;
+
+        emit(VortexEvent::ArtworkPurchased {
+            buyer: *buyer.key,
+            seller: bundle.seller,
+            nft_mint: *nft_mint,
+            price: piece_price,
+            creator_fee: breakdown.creator_fee,
+            artist_royalty: breakdown.artist_royalty,
+            treasury_fee: breakdown.treasury_fee,
+            seller_proceeds: breakdown.seller_proceeds,
+        });
+    }
+
+    close_program_account(bundle_account, seller)?;
+
+    emit(VortexEvent::BundlePurchased {
+        buyer: *buyer.key,
+        seller: bundle.seller,
+        bundle: *bundle_account.key,
+        price: bundle.price,
+    });
+
+    Ok(())
+}
+
+// Return every piece of a bundle to its seller
+pub fn process_cancel_bundle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let seller = next_account_info(account_info_iter)?;
+    let bundle_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify seller is signer
+    if !seller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let bundle = load_bundle(program_id, bundle_account)?;
+    if bundle.seller != *seller.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let bundle_id = bundle.bundle_id.to_le_bytes();
+    let bump = [bundle.bump];
+    let bundle_seeds = bundle.signer_seeds(&bundle_id, &bump);
+
+    for nft_mint in &bundle.nft_mints {
+        let escrow_account = next_account_info(account_info_iter)?;
+        let seller_nft_account = next_account_info(account_info_iter)?;
+
+        let (escrow_key, _) = Pubkey::find_program_address(
+            &[BUNDLE_ESCROW_SEED, bundle_account.key.as_ref(), nft_mint.as_ref()],
+            program_id,
+        );
+        if *escrow_account.key != escrow_key {
+            return Err(VortexError::ListingMismatch.into());
+        }
+        check_token_account(seller_nft_account, seller.key, nft_mint)?;
+
+        transfer_tokens(
+            token_program,
+            escrow_account,
+            seller_nft_account,
+            bundle_account,
+            1,
+            &[&bundle_seeds],
+        )?;
+        close_token_account(token_program, escrow_account, seller, bundle_account, &[&bundle_seeds])?;
+    }
+
+    close_program_account(bundle_account, seller)?;
+
+    emit(VortexEvent::BundleCancelled {
+        seller: *seller.key,
+        bundle: *bundle_account.key,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(size: usize) -> Bundle {
+        Bundle {
+            seller: Pubkey::new_unique(),
+            bundle_id: 7,
+            price: 1_000,
+            nft_mints: (0..size).map(|_| Pubkey::new_unique()).collect(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_full_bundle_fits_account() {
+        assert_eq!(bundle(MAX_BUNDLE_SIZE).try_to_vec().unwrap().len(), Bundle::LEN);
+    }
+
+    #[test]
+    fn test_piece_prices_add_up_to_price() {
+        assert_eq!(bundle(3).piece_prices(), vec![333, 333, 334]);
+        assert_eq!(bundle(MAX_BUNDLE_SIZE).piece_prices().iter().sum::<u64>(), 1_000);
+    }
+
+    #[test]
+    fn test_bundle_address_is_per_id() {
+        let program_id = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+        assert_ne!(
+            find_bundle_address(&program_id, &seller, 1).0,
+            find_bundle_address(&program_id, &seller, 2).0
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token::state::Account as TokenAccount;
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::metadata::assert_collection_member;
+use crate::marketplace::offer::{count_offer, uncount_offer, OFFER_VAULT_SEED};
+use crate::marketplace::provenance::load_or_create_provenance;
+use crate::marketplace::settlement::{check_token_account, load_marketplace_config, settle_sale, SaleAccounts};
+use crate::utils::{close_program_account, close_token_account, create_pda_account, create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+/// Seed of a collection offer PDA, followed by the collection and the buyer
+pub const COLLECTION_OFFER_SEED: &[u8] = b"collection_offer";
+
+/// An offer to buy up to `remaining` NFTs of a collection at `price` each
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CollectionOffer {
+    pub buyer: Pubkey,
+    /// Mint of the Metaplex collection NFT
+    pub collection: Pubkey,
+    /// Paid for each NFT
+    pub price: u64,
+    /// NFTs still wanted; the vault holds `price * remaining`
+    pub remaining: u32,
+    pub expiry: i64,
+    /// Token account holding the payment, owned by the offer
+    pub vault: Pubkey,
+    pub bump: u8,
+}
+
+impl CollectionOffer {
+    pub const LEN: usize = 32 + 32 + 8 + 4 + 8 + 32 + 1;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expiry
+    }
+
+    /// Total still escrowed
+    pub fn escrowed(&self) -> u64 {
+        self.price * self.remaining as u64
+    }
+
+    /// Count one NFT delivered, returning whether the offer is now filled
+    pub fn fill_one(&mut self) -> Result<bool, ProgramError> {
+        if self.remaining == 0 {
+            return Err(VortexError::InvalidState.into());
+        }
+        self.remaining -= 1;
+        Ok(self.remaining == 0)
+    }
+
+    /// Signer seeds of the offer PDA, which owns the vault
+    pub fn signer_seeds<'a>(&'a self, bump: &'a [u8]) -> [&'a [u8]; 4] {
+        [COLLECTION_OFFER_SEED, self.collection.as_ref(), self.buyer.as_ref(), bump]
+    }
+}
+
+/// Address of `buyer`'s offer on `collection`
+pub fn find_collection_offer_address(program_id: &Pubkey, collection: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLLECTION_OFFER_SEED, collection.as_ref(), buyer.as_ref()], program_id)
+}
+
+// Load a collection offer PDA owned by this program
+fn load_collection_offer(program_id: &Pubkey, offer_account: &AccountInfo) -> Result<CollectionOffer, ProgramError> {
+    if offer_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(CollectionOffer::try_from_slice(&offer_account.data.borrow())?)
+}
+
+// Escrow `price * quantity` in a new offer on `collection`
+pub fn process_make_collection_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    collection: Pubkey,
+    price: u64,
+    quantity: u32,
+    expiry: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let buyer = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let payment_mint = next_account_info(account_info_iter)?;
+    let marketplace_config = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let tally_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify buyer is signer
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if price == 0 || quantity == 0 || expiry <= Clock::get()?.unix_timestamp {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let total = price
+        .checked_mul(quantity as u64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let config = load_marketplace_config(program_id, marketplace_config)?;
+    if *payment_mint.key != config.payment_mint {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    let (offer_key, offer_bump) = find_collection_offer_address(program_id, &collection, buyer.key);
+    if *offer_account.key != offer_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !offer_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let (vault_key, vault_bump) =
+        Pubkey::find_program_address(&[OFFER_VAULT_SEED, offer_key.as_ref()], program_id);
+    if *vault.key != vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        buyer,
+        offer_account,
+        system_program,
+        program_id,
+        &rent,
+        CollectionOffer::LEN,
+        &[COLLECTION_OFFER_SEED, collection.as_ref(), buyer.key.as_ref(), &[offer_bump]],
+    )?;
+    create_pda_token_account(
+        buyer,
+        vault,
+        payment_mint,
+        &offer_key,
+        system_program,
+        token_program,
+        &rent,
+        &[OFFER_VAULT_SEED, offer_key.as_ref(), &[vault_bump]],
+    )?;
+
+    transfer_tokens(token_program, buyer_token_account, vault, buyer, total, &[])?;
+
+    let offer = CollectionOffer {
+        buyer: *buyer.key,
+        collection,
+        price,
+        remaining: quantity,
+        expiry,
+        vault: vault_key,
+        bump: offer_bump,
+    };
+    offer.serialize(&mut *offer_account.data.borrow_mut())?;
+
+    // The open offer fixes the royalty of listings in the collection
+    count_offer(program_id, tally_account, &collection, buyer, system_program, &rent)?;
+
+    emit(VortexEvent::CollectionOfferMade {
+        buyer: *buyer.key,
+        collection,
+        price,
+        quantity,
+        expiry,
+    });
+
+    Ok(())
+}
+
+// Sell one NFT of the collection into the offer
+pub fn process_accept_collection_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let seller = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let nft_mint = next_account_info(account_info_iter)?;
+    let metadata_account = next_account_info(account_info_iter)?;
+    let seller_nft_account = next_account_info(account_info_iter)?;
+    let buyer_nft_account = next_account_info(account_info_iter)?;
+    let buyer = next_account_info(account_info_iter)?;
+    let sale = SaleAccounts::next(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let tally_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify seller is signer
+    if !seller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut offer = load_collection_offer(program_id, offer_account)?;
+    if offer.vault != *vault.key || offer.buyer != *buyer.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(VortexError::OfferExpired.into());
+    }
+
+    // Only verified members of the collection can fill the offer
+    assert_collection_member(metadata_account, nft_mint.key, &offer.collection)?;
+
+    let seller_nft = TokenAccount::unpack(&seller_nft_account.data.borrow())?;
+    if seller_nft.owner != *seller.key || seller_nft.mint != *nft_mint.key || seller_nft.amount < 1 {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    check_token_account(buyer_nft_account, &offer.buyer, nft_mint.key)?;
+
+    // An NFT that was never listed is attributed to its verified creator
+    let rent = Rent::from_account_info(rent_account)?;
+    load_or_create_provenance(
+        program_id,
+        sale.provenance_account,
+        metadata_account,
+        nft_mint.key,
+        seller,
+        system_program,
+        &rent,
+    )?;
+
+    let filled = offer.fill_one()?;
+
+    let bump = [offer.bump];
+    let offer_seeds = offer.signer_seeds(&bump);
+    let breakdown = settle_sale(
+        program_id,
+        &sale,
+        account_info_iter,
+        nft_mint.key,
+        seller.key,
+        offer.price,
+        None,
+        vault,
+        offer_account,
+        token_program,
+        &[&offer_seeds],
+    )?;
+
+    transfer_tokens(token_program, seller_nft_account, buyer_nft_account, seller, 1, &[])?;
+
+    if filled {
+        // Return the rent of the vault and offer to the buyer
+        close_token_account(token_program, vault, buyer, offer_account, &[&offer_seeds])?;
+        close_program_account(offer_account, buyer)?;
+        uncount_offer(program_id, tally_account, &offer.collection)?;
+    } else {
+        offer.serialize(&mut *offer_account.data.borrow_mut())?;
+    }
+
+    emit(VortexEvent::ArtworkPurchased {
+        buyer: offer.buyer,
+        seller: *seller.key,
+        nft_mint: *nft_mint.key,
+        price: offer.price,
+        creator_fee: breakdown.creator_fee,
+        artist_royalty: breakdown.artist_royalty,
+        treasury_fee: breakdown.treasury_fee,
+        seller_proceeds: breakdown.seller_proceeds,
+    });
+    emit(VortexEvent::CollectionOfferFilled {
+        buyer: offer.buyer,
+        seller: *seller.key,
+        collection: offer.collection,
+        nft_mint: *nft_mint.key,
+        remaining: offer.remaining,
+    });
+
+    Ok(())
+}
+
+// Refund what is left of a collection offer; only its buyer may do so before it expires
+pub fn process_cancel_collection_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let caller = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let buyer = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let tally_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify caller is signer
+    if !caller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let offer = load_collection_offer(program_id, offer_account)?;
+    if offer.vault != *vault.key || offer.buyer != *buyer.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if *caller.key != offer.buyer && !offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let payment_mint = TokenAccount::unpack(&vault.data.borrow())?.mint;
+    check_token_account(buyer_token_account, &offer.buyer, &payment_mint)?;
+
+    // Everything goes back to the buyer, whoever calls
+    let refund = offer.escrowed();
+    let bump = [offer.bump];
+    let offer_seeds = offer.signer_seeds(&bump);
+    transfer_tokens(
+        token_program,
+        vault,
+        buyer_token_account,
+        offer_account,
+        refund,
+        &[&offer_seeds],
+    )?;
+    close_token_account(token_program, vault, buyer, offer_account, &[&offer_seeds])?;
+    close_program_account(offer_account, buyer)?;
+    uncount_offer(program_id, tally_account, &offer.collection)?;
+
+    emit(VortexEvent::CollectionOfferCancelled {
+        buyer: offer.buyer,
+        collection: offer.collection,
+        refund,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer() -> CollectionOffer {
+        CollectionOffer {
+            buyer: Pubkey::new_unique(),
+            collection: Pubkey::new_unique(),
+            price: 2_500,
+            remaining: 3,
+            expiry: 1_000,
+            vault: Pubkey::new_unique(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_collection_offer_len() {
+        assert_eq!(offer().try_to_vec().unwrap().len(), CollectionOffer::LEN);
+    }
+
+    #[test]
+    fn test_partial_fills_decrement_remaining() {
+        let mut offer = offer();
+        assert_eq!(offer.escrowed(), 7_500);
+        assert!(!offer.fill_one().unwrap());
+        assert_eq!(offer.remaining, 2);
+        assert_eq!(offer.escrowed(), 5_000);
+        assert!(!offer.fill_one().unwrap());
+        assert!(offer.fill_one().unwrap());
+        assert_eq!(offer.escrowed(), 0);
+        assert!(offer.fill_one().is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, load_config, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::utils::{close_program_account, create_pda_account};
+use crate::VortexError;
+
+/// Seed of an accepted mint PDA, followed by the mint
+pub const ACCEPTED_MINT_SEED: &[u8] = b"accepted_mint";
+
+/// Native SOL is accepted under the wrapped SOL mint but paid in lamports
+pub fn is_native_sol(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::id()
+}
+
+/// A currency listings may be settled in besides the marketplace payment token
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AcceptedMint {
+    pub mint: Pubkey,
+    /// Wallet receiving the treasury fee of sales in this currency
+    pub treasury: Pubkey,
+    pub bump: u8,
+}
+
+impl AcceptedMint {
+    pub const LEN: usize = 32 + 32 + 1;
+}
+
+/// Address of the accepted mint account of `mint`
+pub fn find_accepted_mint_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ACCEPTED_MINT_SEED, mint.as_ref()], program_id)
+}
+
+/// Load the accepted mint account of `mint`, failing if `mint` isn't accepted
+pub fn load_accepted_mint(
+    program_id: &Pubkey,
+    accepted_mint_account: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<AcceptedMint, ProgramError> {
+    if *accepted_mint_account.key != find_accepted_mint_address(program_id, mint).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if accepted_mint_account.owner != program_id || accepted_mint_account.data_is_empty() {
+        return Err(VortexError::CurrencyNotAccepted.into());
+    }
+    Ok(AcceptedMint::try_from_slice(&accepted_mint_account.data.borrow())?)
+}
+
+// Accept `mint` for new listings
+pub fn process_add_accepted_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    treasury: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let accepted_mint_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if load_config(program_config)?.admin != *admin.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let (accepted_mint_key, accepted_mint_bump) = find_accepted_mint_address(program_id, &mint);
+    if *accepted_mint_account.key != accepted_mint_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !accepted_mint_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        admin,
+        accepted_mint_account,
+        system_program,
+        program_id,
+        &rent,
+        AcceptedMint::LEN,
+        &[ACCEPTED_MINT_SEED, mint.as_ref(), &[accepted_mint_bump]],
+    )?;
+
+    let accepted_mint = AcceptedMint {
+        mint,
+        treasury,
+        bump: accepted_mint_bump,
+    };
+    accepted_mint.serialize(&mut *accepted_mint_account.data.borrow_mut())?;
+
+    emit(VortexEvent::AcceptedMintAdded {
+        mint,
+        treasury,
+    });
+
+    Ok(())
+}
+
+// Stop accepting `mint`; listings in it can then only be cancelled
+pub fn process_remove_accepted_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let accepted_mint_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if load_config(program_config)?.admin != *admin.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    load_accepted_mint(program_id, accepted_mint_account, &mint)?;
+    close_program_account(accepted_mint_account, admin)?;
+
+    emit(VortexEvent::AcceptedMintRemoved {
+        mint,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepted_mint_len() {
+        let accepted_mint = AcceptedMint {
+            mint: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(accepted_mint.try_to_vec().unwrap().len(), AcceptedMint::LEN);
+    }
+
+    #[test]
+    fn test_native_sol_is_the_wrapped_sol_mint() {
+        assert!(is_native_sol(&spl_token::native_mint::id()));
+        assert!(!is_native_sol(&Pubkey::new_unique()));
+    }
+}
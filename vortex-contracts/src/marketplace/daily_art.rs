@@ -0,0 +1,245 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::provenance::{create_provenance, load_provenance, PROVENANCE_SEED};
+use crate::roles::{require_role, Role};
+use crate::utils::{create_nft, create_pda_account};
+use crate::VortexError;
+
+/// Seed of a daily art account, followed by its date
+pub const DAILY_ART_SEED: &[u8] = b"daily_art";
+
+/// Most artists that can share a single daily piece
+pub const MAX_DAILY_ARTISTS: usize = 16;
+
+/// One TOLA-ART daily piece and the artists sharing its sales
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DailyArt {
+    /// Generation date as `YYYYMMDD`
+    pub date: u32,
+    pub nft_mint: Pubkey,
+    pub artists: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl DailyArt {
+    pub const LEN: usize = 4 + 32 + 4 + 32 * MAX_DAILY_ARTISTS + 1;
+
+    /// Add `artist` unless already participating
+    pub fn add_artist(&mut self, artist: Pubkey) -> ProgramResult {
+        if self.artists.contains(&artist) {
+            return Err(VortexError::InvalidState.into());
+        }
+        if self.artists.len() >= MAX_DAILY_ARTISTS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        self.artists.push(artist);
+        Ok(())
+    }
+}
+
+/// Address of the daily art account of `date`
+pub fn find_daily_art_address(program_id: &Pubkey, date: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DAILY_ART_SEED, &date.to_le_bytes()], program_id)
+}
+
+/// Load a daily art account
+pub fn load_daily_art(program_id: &Pubkey, daily_art_account: &AccountInfo) -> Result<DailyArt, ProgramError> {
+    if daily_art_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(DailyArt::deserialize(&mut &daily_art_account.data.borrow()[..])?)
+}
+
+// Mint the daily piece to the minter and record its artists
+pub fn process_mint_daily_art(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    date: u32,
+    artists: Vec<Pubkey>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let minter = next_account_info(account_info_iter)?;
+    let minter_membership = next_account_info(account_info_iter)?;
+    let daily_art_account = next_account_info(account_info_iter)?;
+    let nft_mint = next_account_info(account_info_iter)?;
+    let minter_nft_account = next_account_info(account_info_iter)?;
+    let provenance_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Only minters may create daily art
+    require_role(minter, minter_membership, Role::Minter)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *associated_token_program.key != spl_associated_token_account::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (daily_art_key, daily_art_bump) = find_daily_art_address(program_id, date);
+    if *daily_art_account.key != daily_art_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !daily_art_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let (provenance_key, provenance_bump) =
+        Pubkey::find_program_address(&[PROVENANCE_SEED, nft_mint.key.as_ref()], program_id);
+    if *provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut daily_art = DailyArt {
+        date,
+        nft_mint: *nft_mint.key,
+        artists: Vec::new(),
+        bump: daily_art_bump,
+    };
+    for artist in artists {
+        daily_art.add_artist(artist)?;
+    }
+    if daily_art.artists.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+
+    create_nft(
+        minter,
+        nft_mint,
+        minter,
+        minter_nft_account,
+        system_program,
+        token_program,
+        associated_token_program,
+        &rent,
+    )?;
+
+    create_pda_account(
+        minter,
+        daily_art_account,
+        system_program,
+        program_id,
+        &rent,
+        DailyArt::LEN,
+        &[DAILY_ART_SEED, &date.to_le_bytes(), &[daily_art_bump]],
+    )?;
+    daily_art.serialize(&mut *daily_art_account.data.borrow_mut())?;
+
+    create_provenance(
+        program_id,
+        provenance_account,
+        provenance_bump,
+        nft_mint.key,
+        minter.key,
+        Some(daily_art_key),
+        minter,
+        system_program,
+        &rent,
+    )?;
+
+    emit(VortexEvent::DailyArtMinted {
+        date,
+        nft_mint: *nft_mint.key,
+        artist_count: daily_art.artists.len() as u8,
+    });
+
+    Ok(())
+}
+
+// Add an artist to a daily piece that has not been sold yet
+pub fn process_add_participating_artist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    date: u32,
+    artist: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let minter = next_account_info(account_info_iter)?;
+    let minter_membership = next_account_info(account_info_iter)?;
+    let daily_art_account = next_account_info(account_info_iter)?;
+    let provenance_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Only minters may change the artists
+    require_role(minter, minter_membership, Role::Minter)?;
+
+    let (daily_art_key, _) = find_daily_art_address(program_id, date);
+    if *daily_art_account.key != daily_art_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut daily_art = load_daily_art(program_id, daily_art_account)?;
+    let (provenance_key, _) =
+        Pubkey::find_program_address(&[PROVENANCE_SEED, daily_art.nft_mint.as_ref()], program_id);
+    if *provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Artists are fixed once the first sale has paid them
+    let provenance = load_provenance(program_id, provenance_account, &daily_art.nft_mint)?;
+    if provenance.sale_count > 0 {
+        return Err(VortexError::InvalidState.into());
+    }
+
+    daily_art.add_artist(artist)?;
+    daily_art.serialize(&mut *daily_art_account.data.borrow_mut())?;
+
+    emit(VortexEvent::ParticipatingArtistAdded {
+        date,
+        artist,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily_art() -> DailyArt {
+        DailyArt {
+            date: 20250101,
+            nft_mint: Pubkey::new_unique(),
+            artists: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_add_artist_rejects_duplicates() {
+        let mut art = daily_art();
+        let artist = Pubkey::new_unique();
+        art.add_artist(artist).unwrap();
+        assert!(art.add_artist(artist).is_err());
+        assert_eq!(art.artists.len(), 1);
+    }
+
+    #[test]
+    fn test_artist_limit_fits_account() {
+        let mut art = daily_art();
+        for _ in 0..MAX_DAILY_ARTISTS {
+            art.add_artist(Pubkey::new_unique()).unwrap();
+        }
+        assert!(art.add_artist(Pubkey::new_unique()).is_err());
+        assert_eq!(art.try_to_vec().unwrap().len(), DailyArt::LEN);
+    }
+}
@@ -0,0 +1,439 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    keccak,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::provenance::{create_provenance, PROVENANCE_SEED};
+use crate::marketplace::settlement::{settle_sale, SaleAccounts};
+use crate::utils::{create_nft, create_pda_account};
+use crate::VortexError;
+
+/// Seed of a drop PDA, followed by the creator and the drop id
+pub const DROP_SEED: &[u8] = b"drop";
+/// Seed of a wallet's mint counter, followed by the drop, the phase index and the wallet
+pub const DROP_MINT_COUNTER_SEED: &[u8] = b"drop_mint_counter";
+
+/// Most phases a single drop can have
+pub const MAX_DROP_PHASES: usize = 4;
+
+/// One sale phase of a drop, running until the next phase starts
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct DropPhase {
+    pub start_time: i64,
+    pub price: u64,
+    /// Root of the keccak merkle tree of allowlisted wallets, `None` for a public phase
+    pub merkle_root: Option<[u8; 32]>,
+    /// Most NFTs one wallet can mint during this phase
+    pub wallet_cap: u32,
+}
+
+impl DropPhase {
+    pub const LEN: usize = 8 + 8 + 33 + 4;
+}
+
+/// A collection launch minting new NFTs in phases
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Drop {
+    /// Receives the primary sales and the resale royalties of every NFT
+    pub creator: Pubkey,
+    pub drop_id: u64,
+    pub max_supply: u32,
+    pub minted: u32,
+    /// Ordered by strictly increasing start time
+    pub phases: Vec<DropPhase>,
+    pub bump: u8,
+}
+
+impl Drop {
+    pub const LEN: usize = 32 + 8 + 4 + 4 + 4 + DropPhase::LEN * MAX_DROP_PHASES + 1;
+
+    /// Index of the phase running at `now`, if the drop has started
+    pub fn active_phase(&self, now: i64) -> Option<usize> {
+        self.phases.iter().rposition(|phase| phase.start_time <= now)
+    }
+}
+
+/// Per-wallet mint count of one drop phase
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DropMintCounter {
+    pub count: u32,
+    pub bump: u8,
+}
+
+impl DropMintCounter {
+    pub const LEN: usize = 4 + 1;
+}
+
+/// Address of `creator`'s drop `drop_id`
+pub fn find_drop_address(program_id: &Pubkey, creator: &Pubkey, drop_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DROP_SEED, creator.as_ref(), &drop_id.to_le_bytes()], program_id)
+}
+
+/// Address of `wallet`'s mint counter for phase `phase` of `drop`
+pub fn find_drop_mint_counter_address(
+    program_id: &Pubkey,
+    drop: &Pubkey,
+    phase: u8,
+    wallet: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DROP_MINT_COUNTER_SEED, drop.as_ref(), &[phase], wallet.as_ref()],
+        program_id,
+    )
+}
+
+/// Verify `wallet` is a leaf of the merkle tree with root `root`.
+///
+/// Leaves are `keccak(wallet)` and each pair of nodes is hashed in sorted
+/// order, so proofs carry no left/right flags.
+pub fn verify_allowlist_proof(root: &[u8; 32], wallet: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let mut node = keccak::hashv(&[wallet.as_ref()]).to_bytes();
+    for sibling in proof {
+        node = if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        };
+    }
+    node == *root
+}
+
+// Phases must be non-empty, capped and start in strictly increasing order
+fn validate_phases(phases: &[DropPhase]) -> ProgramResult {
+    if phases.is_empty() || phases.len() > MAX_DROP_PHASES {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if phases.iter().any(|phase| phase.wallet_cap == 0) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if phases.windows(2).any(|pair| pair[0].start_time >= pair[1].start_time) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+// Create a drop with its sale phases
+pub fn process_create_drop(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    drop_id: u64,
+    max_supply: u32,
+    phases: Vec<DropPhase>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let creator = next_account_info(account_info_iter)?;
+    let drop_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify creator is signer
+    if !creator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if max_supply == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    validate_phases(&phases)?;
+
+    let (drop_key, drop_bump) = find_drop_address(program_id, creator.key, drop_id);
+    if *drop_account.key != drop_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !drop_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        creator,
+        drop_account,
+        system_program,
+        program_id,
+        &rent,
+        Drop::LEN,
+        &[DROP_SEED, creator.key.as_ref(), &drop_id.to_le_bytes(), &[drop_bump]],
+    )?;
+
+    let drop = Drop {
+        creator: *creator.key,
+        drop_id,
+        max_supply,
+        minted: 0,
+        phases,
+        bump: drop_bump,
+    };
+    drop.serialize(&mut *drop_account.data.borrow_mut())?;
+
+    emit(VortexEvent::DropCreated {
+        creator: *creator.key,
+        drop: drop_key,
+        max_supply,
+        phase_count: drop.phases.len() as u8,
+    });
+
+    Ok(())
+}
+
+// Mint one NFT of a drop to the buyer as a primary sale
+pub fn process_mint_from_drop(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_price: u64,
+    proof: Vec<[u8; 32]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let buyer = next_account_info(account_info_iter)?;
+    let drop_account = next_account_info(account_info_iter)?;
+    let counter_account = next_account_info(account_info_iter)?;
+    let nft_mint = next_account_info(account_info_iter)?;
+    let buyer_nft_account = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let sale = SaleAccounts::next(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify buyer is signer
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *associated_token_program.key != spl_associated_token_account::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if drop_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut drop = Drop::deserialize(&mut &drop_account.data.borrow()[..])?;
+    if drop.minted >= drop.max_supply {
+        return Err(VortexError::DropSoldOut.into());
+    }
+
+    let phase_index = drop
+        .active_phase(Clock::get()?.unix_timestamp)
+        .ok_or(VortexError::InvalidState)?;
+    let phase = drop.phases[phase_index].clone();
+    if phase.price > max_price {
+        return Err(VortexError::PriceExceedsMaximum.into());
+    }
+
+    // Allowlist phases only mint to wallets in the merkle tree
+    if let Some(root) = &phase.merkle_root {
+        if !verify_allowlist_proof(root, buyer.key, &proof) {
+            return Err(VortexError::NotAllowlisted.into());
+        }
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+
+    // Count the mint against the wallet's cap for this phase
+    let (counter_key, counter_bump) =
+        find_drop_mint_counter_address(program_id, drop_account.key, phase_index as u8, buyer.key);
+    if *counter_account.key != counter_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut counter = if counter_account.data_is_empty() {
+        create_pda_account(
+            buyer,
+            counter_account,
+            system_program,
+            program_id,
+            &rent,
+            DropMintCounter::LEN,
+            &[
+                DROP_MINT_COUNTER_SEED,
+                drop_account.key.as_ref(),
+                &[phase_index as u8],
+                buyer.key.as_ref(),
+                &[counter_bump],
+            ],
+        )?;
+        DropMintCounter {
+            count: 0,
+            bump: counter_bump,
+        }
+    } else {
+        if counter_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        DropMintCounter::try_from_slice(&counter_account.data.borrow())?
+    };
+    if counter.count >= phase.wallet_cap {
+        return Err(VortexError::MintCapReached.into());
+    }
+    counter.count += 1;
+    counter.serialize(&mut *counter_account.data.borrow_mut())?;
+
+    let (provenance_key, provenance_bump) =
+        Pubkey::find_program_address(&[PROVENANCE_SEED, nft_mint.key.as_ref()], program_id);
+    if *sale.provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    create_nft(
+        buyer,
+        nft_mint,
+        buyer,
+        buyer_nft_account,
+        system_program,
+        token_program,
+        associated_token_program,
+        &rent,
+    )?;
+
+    // The creator owns the NFT's history, so its resales pay them the royalty
+    create_provenance(
+        program_id,
+        sale.provenance_account,
+        provenance_bump,
+        nft_mint.key,
+        &drop.creator,
+        None,
+        buyer,
+        system_program,
+        &rent,
+    )?;
+
+    let breakdown = settle_sale(
+        program_id,
+        &sale,
+        account_info_iter,
+        nft_mint.key,
+        &drop.creator,
+        phase.price,
+        None,
+        buyer_token_account,
+        buyer,
+        token_program,
+        &[],
+    )?;
+
+    drop.minted += 1;
+    drop.serialize(&mut *drop_account.data.borrow_mut())?;
+
+    emit(VortexEvent::DropMinted {
+        buyer: *buyer.key,
+        drop: *drop_account.key,
+        nft_mint: *nft_mint.key,
+        phase: phase_index as u8,
+        minted: drop.minted,
+    });
+    emit(VortexEvent::ArtworkPurchased {
+        buyer: *buyer.key,
+        seller: drop.creator,
+        nft_mint: *nft_mint.key,
+        price: phase.price,
+        creator_fee: breakdown.creator_fee,
+        artist_royalty: breakdown.artist_royalty,
+        treasury_fee: breakdown.treasury_fee,
+        seller_proceeds: breakdown.seller_proceeds,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phase(start_time: i64, merkle_root: Option<[u8; 32]>) -> DropPhase {
+        DropPhase {
+            start_time,
+            price: 1_000,
+            merkle_root,
+            wallet_cap: 2,
+        }
+    }
+
+    fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        if a <= b {
+            keccak::hashv(&[a, b]).to_bytes()
+        } else {
+            keccak::hashv(&[b, a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn test_drop_len() {
+        let drop = Drop {
+            creator: Pubkey::new_unique(),
+            drop_id: 1,
+            max_supply: 100,
+            minted: 0,
+            phases: (0..MAX_DROP_PHASES as i64).map(|i| phase(i, Some([7; 32]))).collect(),
+            bump: 255,
+        };
+        assert_eq!(drop.try_to_vec().unwrap().len(), Drop::LEN);
+        let counter = DropMintCounter { count: 1, bump: 255 };
+        assert_eq!(counter.try_to_vec().unwrap().len(), DropMintCounter::LEN);
+    }
+
+    #[test]
+    fn test_active_phase() {
+        let drop = Drop {
+            creator: Pubkey::new_unique(),
+            drop_id: 1,
+            max_supply: 100,
+            minted: 0,
+            phases: vec![phase(100, Some([7; 32])), phase(200, None)],
+            bump: 255,
+        };
+        assert_eq!(drop.active_phase(99), None);
+        assert_eq!(drop.active_phase(100), Some(0));
+        assert_eq!(drop.active_phase(199), Some(0));
+        assert_eq!(drop.active_phase(200), Some(1));
+        assert_eq!(drop.active_phase(i64::MAX), Some(1));
+    }
+
+    #[test]
+    fn test_validate_phases() {
+        assert!(validate_phases(&[phase(100, None), phase(200, None)]).is_ok());
+        assert!(validate_phases(&[]).is_err());
+        assert!(validate_phases(&[phase(200, None), phase(200, None)]).is_err());
+        assert!(validate_phases(&[phase(200, None), phase(100, None)]).is_err());
+        let mut uncapped = phase(100, None);
+        uncapped.wallet_cap = 0;
+        assert!(validate_phases(&[uncapped]).is_err());
+        let too_many: Vec<_> = (0..=MAX_DROP_PHASES as i64).map(|i| phase(i, None)).collect();
+        assert!(validate_phases(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_allowlist_proof() {
+        let wallets: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets
+            .iter()
+            .map(|wallet| keccak::hashv(&[wallet.as_ref()]).to_bytes())
+            .collect();
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let root = hash_pair(&left, &leaves[2]);
+
+        assert!(verify_allowlist_proof(&root, &wallets[0], &[leaves[1], leaves[2]]));
+        assert!(verify_allowlist_proof(&root, &wallets[1], &[leaves[0], leaves[2]]));
+        assert!(verify_allowlist_proof(&root, &wallets[2], &[left]));
+        assert!(!verify_allowlist_proof(&root, &wallets[2], &[leaves[0]]));
+        assert!(!verify_allowlist_proof(&root, &Pubkey::new_unique(), &[left]));
+    }
+}
@@ -0,0 +1,150 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::{open_listing, ArtworkListing, ListingAccounts, ListingMode};
+
+/// Price schedule of a declining-price listing
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct DutchAuction {
+    pub start_price: u64,
+    /// Floor reached at `end_time` and kept afterwards
+    pub end_price: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+impl DutchAuction {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    /// Price at `now`, decaying linearly from `start_price` to `end_price`
+    pub fn price_at(&self, now: i64) -> u64 {
+        if now <= self.start_time {
+            return self.start_price;
+        }
+        if now >= self.end_time {
+            return self.end_price;
+        }
+        let elapsed = (now - self.start_time) as u128;
+        let duration = (self.end_time - self.start_time) as u128;
+        let decay = (self.start_price - self.end_price) as u128 * elapsed / duration;
+        self.start_price - decay as u64
+    }
+}
+
+/// Price a buyer pays for `listing` at `now`
+pub fn current_price(listing: &ArtworkListing, now: i64) -> u64 {
+    match &listing.mode {
+        ListingMode::Dutch(dutch) => dutch.price_at(now),
+        _ => listing.price,
+    }
+}
+
+/// Quote the current price of a listing from its raw account data.
+///
+/// Meant for clients, which should pass the quote as `max_price` when
+/// purchasing so a stale quote can never overpay.
+pub fn quote_price(listing_data: &[u8], now: i64) -> Result<u64, ProgramError> {
+    let listing = ArtworkListing::deserialize(&mut &listing_data[..])?;
+    Ok(current_price(&listing, now))
+}
+
+// Escrow the NFT and list it at a declining price
+pub fn process_create_dutch_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    start_price: u64,
+    end_price: u64,
+    start_time: i64,
+    end_time: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let listing_accounts = ListingAccounts::next(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    if start_price < end_price || end_time <= start_time || end_time <= Clock::get()?.unix_timestamp {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let dutch = DutchAuction {
+        start_price,
+        end_price,
+        start_time,
+        end_time,
+    };
+    open_listing(program_id, &listing_accounts, start_price, 0, None, None, ListingMode::Dutch(dutch))?;
+
+    emit(VortexEvent::DutchAuctionCreated {
+        seller: *listing_accounts.seller.key,
+        nft_mint: *listing_accounts.nft_mint.key,
+        start_price,
+        end_price,
+        start_time,
+        end_time,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dutch() -> DutchAuction {
+        DutchAuction {
+            start_price: 10_000,
+            end_price: 2_000,
+            start_time: 1_000,
+            end_time: 2_000,
+        }
+    }
+
+    #[test]
+    fn test_price_decays_linearly() {
+        let dutch = dutch();
+        assert_eq!(dutch.price_at(0), 10_000);
+        assert_eq!(dutch.price_at(1_000), 10_000);
+        assert_eq!(dutch.price_at(1_250), 8_000);
+        assert_eq!(dutch.price_at(1_500), 6_000);
+        assert_eq!(dutch.price_at(1_999), 2_008);
+        assert_eq!(dutch.price_at(2_000), 2_000);
+        assert_eq!(dutch.price_at(i64::MAX), 2_000);
+    }
+
+    #[test]
+    fn test_flat_schedule() {
+        let mut dutch = dutch();
+        dutch.end_price = dutch.start_price;
+        assert_eq!(dutch.price_at(1_500), 10_000);
+    }
+
+    #[test]
+    fn test_quote_price_reads_account_data() {
+        let listing = ArtworkListing {
+            seller: Pubkey::new_unique(),
+            nft_mint: Pubkey::new_unique(),
+            price: 10_000,
+            royalty_percentage: 0,
+            is_active: true,
+            escrow: Pubkey::new_unique(),
+            bump: 255,
+            nonce: 0,
+            expires_at: None,
+            payment_mint: None,
+            mode: ListingMode::Dutch(dutch()),
+        };
+        let mut data = listing.try_to_vec().unwrap();
+        data.resize(ArtworkListing::LEN, 0);
+        assert_eq!(quote_price(&data, 1_500).unwrap(), 6_000);
+    }
+}
@@ -0,0 +1,393 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token::state::{Account as TokenAccount, Mint};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::provenance::{create_provenance, is_verified_artist, PROVENANCE_SEED};
+use crate::marketplace::settlement::{settle_sale, SaleAccounts};
+use crate::utils::{create_nft, create_pda_account};
+use crate::VortexError;
+
+/// Seed of an edition PDA, followed by the master NFT mint
+pub const EDITION_SEED: &[u8] = b"edition";
+/// Seed of a print record, followed by the edition and the print number
+pub const EDITION_PRINT_SEED: &[u8] = b"edition_print";
+
+/// A master artwork sold as a limited run of numbered prints
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Edition {
+    /// Receives the primary sale of every print and the resale royalties
+    pub artist: Pubkey,
+    pub master_mint: Pubkey,
+    /// Primary price of one print
+    pub price: u64,
+    pub max_supply: u32,
+    /// Prints minted so far, which is also the number of the latest print
+    pub supply: u32,
+    pub bump: u8,
+}
+
+impl Edition {
+    pub const LEN: usize = 32 + 32 + 8 + 4 + 4 + 1;
+
+    /// Reserve the next print number, numbering from 1
+    pub fn next_print(&mut self) -> Result<u32, ProgramError> {
+        if self.supply >= self.max_supply {
+            return Err(VortexError::EditionSoldOut.into());
+        }
+        self.supply += 1;
+        Ok(self.supply)
+    }
+}
+
+/// Links a print NFT to its edition and number
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct EditionPrint {
+    pub edition: Pubkey,
+    pub number: u32,
+    pub nft_mint: Pubkey,
+    pub bump: u8,
+}
+
+impl EditionPrint {
+    pub const LEN: usize = 32 + 4 + 32 + 1;
+}
+
+/// Address of the edition of `master_mint`
+pub fn find_edition_address(program_id: &Pubkey, master_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EDITION_SEED, master_mint.as_ref()], program_id)
+}
+
+/// Address of the record of print `number` of `edition`
+pub fn find_edition_print_address(program_id: &Pubkey, edition: &Pubkey, number: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EDITION_PRINT_SEED, edition.as_ref(), &number.to_le_bytes()], program_id)
+}
+
+// Open an edition of a master artwork held by the artist
+pub fn process_create_edition(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    price: u64,
+    max_supply: u32,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let artist = next_account_info(account_info_iter)?;
+    let edition_account = next_account_info(account_info_iter)?;
+    let master_mint = next_account_info(account_info_iter)?;
+    let artist_master_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let master_provenance_account = next_account_info(account_info_iter)?;
+    let master_metadata_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify artist is signer
+    if !artist.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if max_supply == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // The master must be an NFT, not a unit of some fungible token
+    if *master_mint.owner != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mint = Mint::unpack(&master_mint.data.borrow())?;
+    if mint.decimals != 0 || mint.supply != 1 {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    // Only the artist holding the master artwork can open its edition; a
+    // collector who bought it can't print it
+    let master = TokenAccount::unpack(&artist_master_account.data.borrow())?;
+    if master.owner != *artist.key || master.mint != *master_mint.key || master.amount < 1 {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if !is_verified_artist(
+        program_id,
+        master_provenance_account,
+        master_metadata_account,
+        master_mint.key,
+        artist.key,
+    )? {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let (edition_key, edition_bump) = find_edition_address(program_id, master_mint.key);
+    if *edition_account.key != edition_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !edition_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        artist,
+        edition_account,
+        system_program,
+        program_id,
+        &rent,
+        Edition::LEN,
+        &[EDITION_SEED, master_mint.key.as_ref(), &[edition_bump]],
+    )?;
+
+    let edition = Edition {
+        artist: *artist.key,
+        master_mint: *master_mint.key,
+        price,
+        max_supply,
+        supply: 0,
+        bump: edition_bump,
+    };
+    edition.serialize(&mut *edition_account.data.borrow_mut())?;
+
+    emit(VortexEvent::EditionCreated {
+        artist: *artist.key,
+        master_mint: *master_mint.key,
+        price,
+        max_supply,
+    });
+
+    Ok(())
+}
+
+// Mint the next numbered print to the buyer as a primary sale
+pub fn process_mint_edition_print(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_price: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let buyer = next_account_info(account_info_iter)?;
+    let edition_account = next_account_info(account_info_iter)?;
+    let print_account = next_account_info(account_info_iter)?;
+    let print_mint = next_account_info(account_info_iter)?;
+    let buyer_print_account = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let sale = SaleAccounts::next(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let associated_token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify buyer is signer
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *associated_token_program.key != spl_associated_token_account::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if edition_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut edition = Edition::try_from_slice(&edition_account.data.borrow())?;
+    if edition.price > max_price {
+        return Err(VortexError::PriceExceedsMaximum.into());
+    }
+    let number = edition.next_print()?;
+
+    let (print_key, print_bump) = find_edition_print_address(program_id, edition_account.key, number);
+    if *print_account.key != print_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (provenance_key, provenance_bump) =
+        Pubkey::find_program_address(&[PROVENANCE_SEED, print_mint.key.as_ref()], program_id);
+    if *sale.provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+
+    create_nft(
+        buyer,
+        print_mint,
+        buyer,
+        buyer_print_account,
+        system_program,
+        token_program,
+        associated_token_program,
+        &rent,
+    )?;
+
+    // The artist owns the print's history, so its resales pay them the royalty
+    create_provenance(
+        program_id,
+        sale.provenance_account,
+        provenance_bump,
+        print_mint.key,
+        &edition.artist,
+        None,
+        buyer,
+        system_program,
+        &rent,
+    )?;
+
+    create_pda_account(
+        buyer,
+        print_account,
+        system_program,
+        program_id,
+        &rent,
+        EditionPrint::LEN,
+        &[EDITION_PRINT_SEED, edition_account.key.as_ref(), &number.to_le_bytes(), &[print_bump]],
+    )?;
+    let print = EditionPrint {
+        edition: *edition_account.key,
+        number,
+        nft_mint: *print_mint.key,
+        bump: print_bump,
+    };
+    print.serialize(&mut *print_account.data.borrow_mut())?;
+
+    let breakdown = settle_sale(
+        program_id,
+        &sale,
+        account_info_iter,
+        print_mint.key,
+        &edition.artist,
+        edition.price,
+        None,
+        buyer_token_account,
+        buyer,
+        token_program,
+        &[],
+    )?;
+
+    edition.serialize(&mut *edition_account.data.borrow_mut())?;
+
+    emit(VortexEvent::EditionPrintMinted {
+        buyer: *buyer.key,
+        master_mint: edition.master_mint,
+        nft_mint: *print_mint.key,
+        number,
+        max_supply: edition.max_supply,
+    });
+    emit(VortexEvent::ArtworkPurchased {
+        buyer: *buyer.key,
+        seller: edition.artist,
+        nft_mint: *print_mint.key,
+        price: edition.price,
+        creator_fee: breakdown.creator_fee,
+        artist_royalty: breakdown.artist_royalty,
+        treasury_fee: breakdown.treasury_fee,
+        seller_proceeds: breakdown.seller_proceeds,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::system_program;
+    use crate::marketplace::{process_instruction, MarketplaceInstruction};
+    use crate::test_utils::{self, empty, initialized_config, mint, program, rent_sysvar, run, token_account, wallet};
+
+    fn edition() -> Edition {
+        Edition {
+            artist: Pubkey::new_unique(),
+            master_mint: Pubkey::new_unique(),
+            price: 1_000,
+            max_supply: 2,
+            supply: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_edition_len() {
+        assert_eq!(edition().try_to_vec().unwrap().len(), Edition::LEN);
+        let print = EditionPrint {
+            edition: Pubkey::new_unique(),
+            number: 1,
+            nft_mint: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(print.try_to_vec().unwrap().len(), EditionPrint::LEN);
+    }
+
+    #[test]
+    fn test_prints_are_numbered_up_to_max_supply() {
+        let mut edition = edition();
+        assert_eq!(edition.next_print().unwrap(), 1);
+        assert_eq!(edition.next_print().unwrap(), 2);
+        assert_eq!(
+            edition.next_print().unwrap_err(),
+            VortexError::EditionSoldOut.into()
+        );
+        assert_eq!(edition.supply, 2);
+    }
+
+    #[test]
+    fn test_only_the_verified_artist_editions_an_nft() {
+        test_utils::install();
+        let program_id = Pubkey::new_unique();
+        let admin = wallet();
+        let program_config = initialized_config(&admin, &Pubkey::new_unique());
+
+        let create_edition = |holder: &AccountInfo<'static>, master_mint: &AccountInfo<'static>, artist: &Pubkey| {
+            let accounts = [
+                holder.clone(),
+                empty(find_edition_address(&program_id, master_mint.key).0),
+                master_mint.clone(),
+                token_account(master_mint.key, holder.key, 1),
+                program(system_program::id()),
+                rent_sysvar(),
+                program_config.clone(),
+                empty(Pubkey::find_program_address(&[PROVENANCE_SEED, master_mint.key.as_ref()], &program_id).0),
+                test_utils::metadata(master_mint.key, artist),
+            ];
+            let data = MarketplaceInstruction::CreateEdition { price: 1_000, max_supply: 10 }.try_to_vec().unwrap();
+            run(process_instruction, &program_id, &accounts, &data)
+        };
+        let master = |decimals: u8, supply: u64| {
+            let master_mint = mint(&Pubkey::new_unique());
+            let mut state = Mint::unpack(&master_mint.data.borrow()).unwrap();
+            state.decimals = decimals;
+            state.supply = supply;
+            Mint::pack(state, &mut master_mint.data.borrow_mut()).unwrap();
+            master_mint
+        };
+
+        // A collector who bought the artwork can't print it
+        let (artist, collector) = (wallet(), wallet());
+        assert_eq!(
+            create_edition(&collector, &master(0, 1), artist.key).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+        // Nor can one unit of a fungible token stand in for a master
+        assert_eq!(
+            create_edition(&artist, &master(9, 1_000), artist.key).unwrap_err(),
+            VortexError::InvalidAccount.into()
+        );
+        assert_eq!(
+            create_edition(&artist, &master(0, 2), artist.key).unwrap_err(),
+            VortexError::InvalidAccount.into()
+        );
+
+        create_edition(&artist, &master(0, 1), artist.key).unwrap();
+    }
+}
@@ -0,0 +1,207 @@
+use solana_program::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use borsh::BorshDeserialize;
+use crate::VortexError;
+
+/// The Metaplex token metadata program
+pub mod metadata_program {
+    solana_program::declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+/// Seed of a Metaplex metadata account, followed by the metadata program and the mint
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+// Account key of `Metadata` in the metadata program
+pub(crate) const METADATA_V1_KEY: u8 = 4;
+
+#[derive(BorshDeserialize, Debug)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+/// Leading fields of a Metaplex `Metadata` account, up to its collection.
+///
+/// Only what the marketplace needs is read; later fields are ignored.
+#[derive(BorshDeserialize, Debug)]
+pub struct Metadata {
+    pub key: u8,
+    pub update_authority: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<Creator>>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    pub collection: Option<Collection>,
+}
+
+impl Metadata {
+    /// Parse the raw data of a metadata account
+    pub fn parse(data: &[u8]) -> Result<Self, ProgramError> {
+        let metadata = Self::deserialize(&mut &data[..])?;
+        if metadata.key != METADATA_V1_KEY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(metadata)
+    }
+
+    /// The NFT is a verified member of `collection`
+    pub fn is_verified_member_of(&self, collection: &Pubkey) -> bool {
+        matches!(self.collection, Some(Collection { verified: true, key }) if key == *collection)
+    }
+}
+
+/// Address of the metadata account of `mint`
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[METADATA_SEED, metadata_program::id().as_ref(), mint.as_ref()],
+        &metadata_program::id(),
+    )
+}
+
+// Metadata of `mint` read from its metadata account, `None` for an NFT
+// without metadata
+fn load_metadata(metadata_account: &AccountInfo, mint: &Pubkey) -> Result<Option<Metadata>, ProgramError> {
+    if *metadata_account.key != find_metadata_address(mint).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if metadata_account.data_is_empty() {
+        return Ok(None);
+    }
+    if *metadata_account.owner != metadata_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let metadata = Metadata::parse(&metadata_account.data.borrow())?;
+    Ok(Some(metadata).filter(|metadata| metadata.mint == *mint))
+}
+
+/// Verified collection of `mint` read from its metadata account, `None` for
+/// an NFT without metadata or collection
+pub fn verified_collection(metadata_account: &AccountInfo, mint: &Pubkey) -> Result<Option<Pubkey>, ProgramError> {
+    match load_metadata(metadata_account, mint)?.and_then(|metadata| metadata.collection) {
+        Some(Collection { verified: true, key }) => Ok(Some(key)),
+        _ => Ok(None),
+    }
+}
+
+/// Artist of `mint` read from its metadata account: the first verified
+/// creator sharing in its royalties. `None` for an NFT without metadata or
+/// verified creator, whose artist can't be told from whoever holds it.
+pub fn verified_artist(metadata_account: &AccountInfo, mint: &Pubkey) -> Result<Option<Pubkey>, ProgramError> {
+    let creators = load_metadata(metadata_account, mint)?.and_then(|metadata| metadata.creators);
+    Ok(creators
+        .unwrap_or_default()
+        .into_iter()
+        .find(|creator| creator.verified && creator.share > 0)
+        .map(|creator| creator.address))
+}
+
+/// Verify that `mint` is a verified member of `collection` through its
+/// metadata account
+pub fn assert_collection_member(
+    metadata_account: &AccountInfo,
+    mint: &Pubkey,
+    collection: &Pubkey,
+) -> Result<(), ProgramError> {
+    if *metadata_account.owner != metadata_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *metadata_account.key != find_metadata_address(mint).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let metadata = Metadata::parse(&metadata_account.data.borrow())?;
+    if metadata.mint != *mint || !metadata.is_verified_member_of(collection) {
+        return Err(VortexError::NotInCollection.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account, empty, metadata_with_creators};
+
+    fn metadata_data(mint: &Pubkey, collection: Option<(bool, Pubkey)>) -> Vec<u8> {
+        metadata_with_creators(mint, &[(Pubkey::new_unique(), true, 100)], collection)
+    }
+
+    #[test]
+    fn test_parse_verified_collection() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let metadata = Metadata::parse(&metadata_data(&mint, Some((true, collection)))).unwrap();
+        assert_eq!(metadata.mint, mint);
+        assert!(metadata.is_verified_member_of(&collection));
+        assert!(!metadata.is_verified_member_of(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_unverified_or_missing_collection() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let metadata = Metadata::parse(&metadata_data(&mint, Some((false, collection)))).unwrap();
+        assert!(!metadata.is_verified_member_of(&collection));
+        let metadata = Metadata::parse(&metadata_data(&mint, None)).unwrap();
+        assert!(!metadata.is_verified_member_of(&collection));
+    }
+
+    #[test]
+    fn test_rejects_other_account_kinds() {
+        let mut data = metadata_data(&Pubkey::new_unique(), None);
+        data[0] = 6;
+        assert!(Metadata::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_verified_collection_of_account() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let address = find_metadata_address(&mint).0;
+
+        let member = account(address, metadata_program::id(), 1, metadata_data(&mint, Some((true, collection))), false);
+        assert_eq!(verified_collection(&member, &mint).unwrap(), Some(collection));
+        let unverified = account(address, metadata_program::id(), 1, metadata_data(&mint, Some((false, collection))), false);
+        assert_eq!(verified_collection(&unverified, &mint).unwrap(), None);
+        assert_eq!(verified_collection(&empty(address), &mint).unwrap(), None);
+
+        // Metadata can't be forged by another program or at another address
+        let forged = account(address, Pubkey::new_unique(), 1, metadata_data(&mint, Some((true, collection))), false);
+        assert_eq!(verified_collection(&forged, &mint).unwrap_err(), ProgramError::IncorrectProgramId);
+        assert_eq!(
+            verified_collection(&empty(Pubkey::new_unique()), &mint).unwrap_err(),
+            ProgramError::InvalidSeeds
+        );
+    }
+
+    #[test]
+    fn test_verified_artist_of_account() {
+        let mint = Pubkey::new_unique();
+        let address = find_metadata_address(&mint).0;
+        let (candy_machine, artist, unverified) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Skips unverified creators and verified ones without a share
+        let creators = [(unverified, false, 50), (candy_machine, true, 0), (artist, true, 50)];
+        let metadata = account(address, metadata_program::id(), 1, metadata_with_creators(&mint, &creators, None), false);
+        assert_eq!(verified_artist(&metadata, &mint).unwrap(), Some(artist));
+
+        let unverified_only = metadata_with_creators(&mint, &[(unverified, false, 100)], None);
+        let metadata = account(address, metadata_program::id(), 1, unverified_only, false);
+        assert_eq!(verified_artist(&metadata, &mint).unwrap(), None);
+        assert_eq!(verified_artist(&empty(address), &mint).unwrap(), None);
+    }
+}
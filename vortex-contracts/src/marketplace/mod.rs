@@ -1,45 +1,668 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint,
     entrypoint::ProgramResult,
     msg,
     program_error::ProgramError,
+    program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token::state::Account as TokenAccount;
+use crate::config::{assert_not_paused, load_config, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::utils::{close_program_account, close_token_account, create_pda_account, create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+pub mod auction;
+pub mod bundle;
+pub mod collection_offer;
+pub mod currency;
+pub mod daily_art;
+pub mod drop;
+pub mod dutch;
+pub mod edition;
+pub mod metadata;
+pub mod offer;
+pub mod provenance;
+pub mod royalty;
+pub mod settlement;
+
+use auction::Auction;
+use currency::{is_native_sol, load_accepted_mint};
+use dutch::{current_price, DutchAuction};
+use provenance::load_or_create_provenance;
+use settlement::{check_token_account, settle_sale, SaleAccounts};
+
+/// Seed of the `MarketplaceConfig` PDA
+pub const MARKETPLACE_CONFIG_SEED: &[u8] = b"marketplace_config";
+/// Seed of a listing PDA, followed by the seller and the NFT mint
+pub const LISTING_SEED: &[u8] = b"listing";
+/// Seed of a listing's NFT escrow, followed by the seller and the NFT mint
+pub const ESCROW_SEED: &[u8] = b"escrow";
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum MarketplaceInstruction {
     /// List an artwork for sale
     /// 
+    /// Moves the NFT into an escrow token account owned by the listing until
+    /// it is sold or the listing is cancelled. An NFT listed for the first
+    /// time is attributed to its verified creator, see
+    /// `load_or_create_provenance`, and only that artist's first sale of it
+    /// pays the primary split.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` The seller's account
-    /// 1. `[writable]` The listing account
+    /// 0. `[signer, writable]` The seller's account
+    /// 1. `[writable]` The listing account (PDA of `["listing", seller, nft_mint]`)
     /// 2. `[]` The NFT mint account
     /// 3. `[writable]` The seller's NFT account
+    /// 4. `[writable]` The escrow NFT account (PDA of `["escrow", seller, nft_mint]`)
+    /// 5. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 6. `[]` The NFT's metadata account (PDA of `["metadata", metadata program, nft_mint]`)
+    /// 7. `[]` The token program
+    /// 8. `[]` The system program
+    /// 9. `[]` The rent sysvar
+    /// 10. `[]` The program config account (PDA of `["config"]`)
+    /// 11. `[]` Other currencies only: the accepted mint account (PDA of `["accepted_mint", mint]`)
     ListArtwork {
         price: u64,
+        /// Advertised royalty, informational only, see `ArtworkListing::royalty_percentage`
         royalty_percentage: u8,
+        /// Unix timestamp from which the listing can't be bought, `None` for no expiry
+        expires_at: Option<i64>,
+        /// Currency the listing is settled in, `None` for the marketplace
+        /// payment token. Native SOL is the wrapped SOL mint.
+        payment_mint: Option<Pubkey>,
     },
 
     /// Purchase an artwork
     /// 
+    /// Pays the current price of the listing, see `dutch::current_price`, in
+    /// the marketplace payment token and moves the NFT out of escrow to the
+    /// buyer. The original artist's first sale of an NFT is split 5% to
+    /// the Vortex creator, 15% to the treasury and 80% to the artist; resales
+    /// pay 5% to the creator, 15% to the original artist, 15% to the treasury
+    /// and 65% to the seller, see `royalty::get_royalty_breakdown`. Daily art
+    /// shares its artist royalty between all participating artists instead,
+    /// see `royalty::get_daily_art_breakdown`.
+    /// 
+    /// The same split applies in every currency. SOL listings are paid from
+    /// the buyer's wallet straight to the payees' wallets, which then take
+    /// the place of their token accounts. Optional accounts are left out when
+    /// they don't apply.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` The buyer's account
+    /// 0. `[signer, writable]` The buyer's account
     /// 1. `[writable]` The listing account
     /// 2. `[writable]` The seller's token account
-    /// 3. `[writable]` The buyer's token account
-    /// 4. `[writable]` The NFT account
+    /// 3. `[writable]` The buyer's token account, unused for SOL listings
+    /// 4. `[writable]` The buyer's NFT account
+    /// 5. `[writable]` The escrow NFT account
+    /// 6. `[writable]` The Vortex creator's token account
+    /// 7. `[writable]` The treasury token account
+    /// 8. `[writable]` The original artist's token account, unused for daily art or without a verified artist
+    /// 9. `[writable]` The seller's account, refunded the escrow rent
+    /// 10. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 11. `[]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 12. `[]` The token program
+    /// 13. `[]` The program config account (PDA of `["config"]`)
+    /// 14. `[]` Other currencies only: the accepted mint account (PDA of `["accepted_mint", mint]`)
+    /// 15. `[]` SOL listings only: the system program
+    /// 16. `[]` Daily art only: the daily art account
+    /// 17. `[writable]` Daily art only: one token account per participating artist, in order
     PurchaseArtwork {
+        /// Most the buyer is willing to pay; the current price is charged
+        max_price: u64,
+        /// Nonce of the listing version the buyer saw
+        expected_nonce: u64,
+    },
+
+    /// Cancel a listing and return the NFT to the seller
+    /// 
+    /// An auction can only be cancelled before its first bid.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The seller's account
+    /// 1. `[writable]` The listing account
+    /// 2. `[writable]` The seller's NFT account
+    /// 3. `[writable]` The escrow NFT account
+    /// 4. `[]` The token program
+    /// 5. `[]` The program config account (PDA of `["config"]`)
+    /// 6. `[writable]` Auctions only: the bid vault
+    CancelListing {},
+
+    /// Set the fee recipients and payment token of the marketplace
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The config admin
+    /// 1. `[writable]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 2. `[]` The system program
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The program config account (PDA of `["config"]`)
+    InitializeMarketplace {
+        /// Wallet receiving the Vortex creator fee
+        creator: Pubkey,
+        /// Wallet receiving the treasury fee
+        treasury: Pubkey,
+        /// Token sales are settled in, normally TOLA
+        payment_mint: Pubkey,
+    },
+
+    /// Mint the TOLA-ART piece of `date` to the minter
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The account holding the minter role, receives the NFT
+    /// 1. `[]` The minter's role membership (PDA of `["role", role, member]`)
+    /// 2. `[writable]` The daily art account (PDA of `["daily_art", date]`)
+    /// 3. `[signer, writable]` The NFT mint account (uninitialized)
+    /// 4. `[writable]` The minter's associated token account for the NFT
+    /// 5. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 6. `[]` The token program
+    /// 7. `[]` The associated token account program
+    /// 8. `[]` The system program
+    /// 9. `[]` The rent sysvar
+    /// 10. `[]` The program config account (PDA of `["config"]`)
+    MintDailyArt {
+        /// Generation date as `YYYYMMDD`
+        date: u32,
+        artists: Vec<Pubkey>,
+    },
+
+    /// Add an artist to a daily piece before its first sale
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The account holding the minter role
+    /// 1. `[]` The minter's role membership (PDA of `["role", role, member]`)
+    /// 2. `[writable]` The daily art account
+    /// 3. `[]` The provenance account of the daily art NFT
+    /// 4. `[]` The program config account (PDA of `["config"]`)
+    AddParticipatingArtist {
+        date: u32,
+        artist: Pubkey,
+    },
+
+    /// Put an artwork up for an English auction
+    /// 
+    /// Escrows the NFT like `ListArtwork`. A bid placed within
+    /// `extension_window` seconds of the end pushes the end back to
+    /// `extension_window` seconds after the bid. The auction may run for at
+    /// most `MAX_AUCTION_DURATION` and the window is capped at
+    /// `MAX_EXTENSION_WINDOW`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The seller's account
+    /// 1. `[writable]` The listing account (PDA of `["listing", seller, nft_mint]`)
+    /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The seller's NFT account
+    /// 4. `[writable]` The escrow NFT account (PDA of `["escrow", seller, nft_mint]`)
+    /// 5. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 6. `[]` The NFT's metadata account (PDA of `["metadata", metadata program, nft_mint]`)
+    /// 7. `[]` The token program
+    /// 8. `[]` The system program
+    /// 9. `[]` The rent sysvar
+    /// 10. `[writable]` The bid vault (PDA of `["bid_vault", listing]`)
+    /// 11. `[]` The payment mint
+    /// 12. `[]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 13. `[]` The program config account (PDA of `["config"]`)
+    CreateAuction {
+        /// Lowest acceptable first bid
+        reserve: u64,
+        /// Each bid must beat the previous one by this share
+        min_increment_bps: u16,
+        /// Unix timestamp after which the auction can be settled
+        end_time: i64,
+        /// Anti-sniping window in seconds
+        extension_window: i64,
+    },
+
+    /// Bid on an auction
+    /// 
+    /// Escrows the bid in the bid vault and refunds the bid it beats.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The bidder's account
+    /// 1. `[writable]` The listing account
+    /// 2. `[writable]` The bidder's token account
+    /// 3. `[writable]` The bid vault
+    /// 4. `[writable]` The previous highest bidder's token account, unused for the first bid
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    PlaceBid {
+        amount: u64,
+    },
+
+    /// Close an auction that has ended; callable by anyone
+    /// 
+    /// Pays the winning bid out of the bid vault through the same split as
+    /// `PurchaseArtwork` and delivers the NFT to the winner. Without bids the
+    /// NFT goes back to the seller.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The caller
+    /// 1. `[writable]` The listing account
+    /// 2. `[writable]` The escrow NFT account
+    /// 3. `[writable]` The bid vault
+    /// 4. `[writable]` The winner's NFT account, or the seller's without bids
+    /// 5. `[writable]` The seller's account, refunded the escrow and vault rent
+    /// 6. `[writable]` The seller's token account
+    /// 7. `[writable]` The Vortex creator's token account
+    /// 8. `[writable]` The treasury token account
+    /// 9. `[writable]` The original artist's token account, unused for daily art or without a verified artist
+    /// 10. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 11. `[]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 12. `[]` The token program
+    /// 13. `[]` The program config account (PDA of `["config"]`)
+    /// 14. `[]` Daily art only: the daily art account
+    /// 15. `[writable]` Daily art only: one token account per participating artist, in order
+    SettleAuction {},
+
+    /// List an artwork at a price declining from `start_price` to `end_price`
+    /// 
+    /// The price decays linearly between `start_time` and `end_time` and
+    /// stays at `end_price` afterwards. Bought with `PurchaseArtwork`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The seller's account
+    /// 1. `[writable]` The listing account (PDA of `["listing", seller, nft_mint]`)
+    /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The seller's NFT account
+    /// 4. `[writable]` The escrow NFT account (PDA of `["escrow", seller, nft_mint]`)
+    /// 5. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 6. `[]` The NFT's metadata account (PDA of `["metadata", metadata program, nft_mint]`)
+    /// 7. `[]` The token program
+    /// 8. `[]` The system program
+    /// 9. `[]` The rent sysvar
+    /// 10. `[]` The program config account (PDA of `["config"]`)
+    CreateDutchAuction {
+        start_price: u64,
+        end_price: u64,
+        start_time: i64,
+        end_time: i64,
+    },
+
+    /// Offer to buy an NFT, listed or not, escrowing the payment
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer's account
+    /// 1. `[writable]` The offer account (PDA of `["offer", nft_mint, buyer]`)
+    /// 2. `[writable]` The buyer's token account
+    /// 3. `[writable]` The offer vault (PDA of `["offer_vault", offer]`)
+    /// 4. `[]` The payment mint
+    /// 5. `[]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 6. `[]` The token program
+    /// 7. `[]` The system program
+    /// 8. `[]` The rent sysvar
+    /// 9. `[]` The program config account (PDA of `["config"]`)
+    /// 10. `[writable]` The NFT's offer tally (PDA of `["offer_tally", nft_mint]`), counting the open offer
+    MakeOffer {
+        nft_mint: Pubkey,
+        amount: u64,
+        /// Unix timestamp from which the offer can be refunded by anyone
+        expiry: i64,
+    },
+
+    /// Sell an NFT to an offer
+    /// 
+    /// Swaps the NFT for the escrowed payment, which is paid out through the
+    /// same split as `PurchaseArtwork`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT holder
+    /// 1. `[writable]` The offer account
+    /// 2. `[writable]` The offer vault
+    /// 3. `[writable]` The holder's NFT account
+    /// 4. `[writable]` The buyer's NFT account
+    /// 5. `[writable]` The buyer's account, refunded the offer rent
+    /// 6. `[writable]` The holder's token account
+    /// 7. `[writable]` The Vortex creator's token account
+    /// 8. `[writable]` The treasury token account
+    /// 9. `[writable]` The original artist's token account, unused for daily art or without a verified artist
+    /// 10. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 11. `[]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 12. `[]` The token program
+    /// 13. `[]` The system program
+    /// 14. `[]` The rent sysvar
+    /// 15. `[]` The program config account (PDA of `["config"]`)
+    /// 16. `[writable]` The NFT's offer tally (PDA of `["offer_tally", nft_mint]`)
+    /// 17. `[]` The NFT's metadata account (PDA of `["metadata", metadata program, nft_mint]`)
+    /// 18. `[]` Daily art only: the daily art account
+    /// 19. `[writable]` Daily art only: one token account per participating artist, in order
+    AcceptOffer {},
+
+    /// Withdraw an offer and refund the buyer
+    /// 
+    /// Anyone may refund an expired offer; the payment always goes back to
+    /// the buyer.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The buyer, or anyone once the offer expired
+    /// 1. `[writable]` The offer account
+    /// 2. `[writable]` The offer vault
+    /// 3. `[writable]` The buyer's token account
+    /// 4. `[writable]` The buyer's account, refunded the offer rent
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    /// 7. `[writable]` The NFT's offer tally (PDA of `["offer_tally", nft_mint]`)
+    CancelOffer {},
+
+    /// Offer to buy up to `quantity` NFTs of a Metaplex collection at `price`
+    /// each, escrowing `price * quantity`
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer's account
+    /// 1. `[writable]` The collection offer account (PDA of `["collection_offer", collection, buyer]`)
+    /// 2. `[writable]` The buyer's token account
+    /// 3. `[writable]` The offer vault (PDA of `["offer_vault", collection_offer]`)
+    /// 4. `[]` The payment mint
+    /// 5. `[]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 6. `[]` The token program
+    /// 7. `[]` The system program
+    /// 8. `[]` The rent sysvar
+    /// 9. `[]` The program config account (PDA of `["config"]`)
+    /// 10. `[writable]` The collection's offer tally (PDA of `["offer_tally", collection]`), counting the open offer
+    MakeCollectionOffer {
+        /// Mint of the collection NFT
+        collection: Pubkey,
         price: u64,
+        quantity: u32,
+        expiry: i64,
     },
 
-    /// Cancel a listing
+    /// Sell one NFT of the collection into a collection offer
+    /// 
+    /// The NFT must be a verified member of the collection according to its
+    /// Metaplex metadata. Each fill is paid out through the same split as
+    /// `PurchaseArtwork`; the offer closes once its quantity is filled.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT holder
+    /// 1. `[writable]` The collection offer account
+    /// 2. `[writable]` The offer vault
+    /// 3. `[]` The NFT mint
+    /// 4. `[]` The NFT's Metaplex metadata account
+    /// 5. `[writable]` The holder's NFT account
+    /// 6. `[writable]` The buyer's NFT account
+    /// 7. `[writable]` The buyer's account, refunded the offer rent on the last fill
+    /// 8. `[writable]` The holder's token account
+    /// 9. `[writable]` The Vortex creator's token account
+    /// 10. `[writable]` The treasury token account
+    /// 11. `[writable]` The original artist's token account, unused for daily art or without a verified artist
+    /// 12. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    /// 13. `[]` The marketplace config account (PDA of `["marketplace_config"]`)
+    /// 14. `[]` The token program
+    /// 15. `[]` The system program
+    /// 16. `[]` The rent sysvar
+    /// 17. `[]` The program config account (PDA of `["config"]`)
+    /// 18. `[writable]` The collection's offer tally (PDA of `["offer_tally", collection]`)
+    /// 19. `[]` Daily art only: the daily art account
+    /// 20. `[writable]` Daily art only: one token account per participating artist, in order
+    AcceptCollectionOffer {},
+
+    /// Withdraw what is left of a collection offer and refund the buyer
+    /// 
+    /// Anyone may refund an expired offer; the payment always goes back to
+    /// the buyer.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The buyer, or anyone once the offer expired
+    /// 1. `[writable]` The collection offer account
+    /// 2. `[writable]` The offer vault
+    /// 3. `[writable]` The buyer's token account
+    /// 4. `[writable]` The buyer's account, refunded the offer rent
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    /// 7. `[writable]` The collection's offer tally (PDA of `["offer_tally", collection]`)
+    CancelCollectionOffer {},
+
+    /// Change the price, royalty or expiry of a listing in place
+    /// 
+    /// Bumps the listing nonce, so purchases pinned to the old version fail.
+    /// The royalty percentage can't change while the auction has bids or
+    /// offers are open on the NFT or, for a verified collection member, on
+    /// its collection. An auction's reserve can't change once bidding started.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The seller's account
     /// 1. `[writable]` The listing account
-    CancelListing {},
+    /// 2. `[]` The program config account (PDA of `["config"]`)
+    /// 3. `[]` Royalty changes only: the NFT's offer tally (PDA of `["offer_tally", nft_mint]`)
+    /// 4. `[]` Royalty changes only: the NFT's Metaplex metadata account
+    /// 5. `[]` Royalty changes of verified collection members only: the collection's offer tally
+    UpdateListing {
+        new_price: u64,
+        /// New royalty percentage, `None` to keep it
+        royalty_percentage: Option<u8>,
+        /// Unix timestamp from which the listing can't be bought, `None` for no expiry
+        expiry: Option<i64>,
+    },
+
+    /// Close an expired or inactive listing; callable by anyone
+    /// 
+    /// Returns the NFT of an expired listing to the seller, then closes the
+    /// escrow and the listing account and refunds their rent to the seller.
+    /// The listing's nonces are retired in the NFT's provenance, so a
+    /// relisting never accepts purchases signed against them.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The caller
+    /// 1. `[writable]` The listing account
+    /// 2. `[writable]` The seller's account, refunded the rent
+    /// 3. `[writable]` The seller's NFT account, unused for inactive listings
+    /// 4. `[writable]` The escrow NFT account, unused for inactive listings
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    /// 7. `[writable]` The provenance account (PDA of `["provenance", nft_mint]`)
+    CloseExpiredListing {},
+
+    /// Accept `mint` as a listing currency, wrapped SOL standing for native SOL
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The config admin
+    /// 1. `[writable]` The accepted mint account (PDA of `["accepted_mint", mint]`)
+    /// 2. `[]` The system program
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The program config account (PDA of `["config"]`)
+    AddAcceptedMint {
+        mint: Pubkey,
+        /// Wallet receiving the treasury fee of sales in this currency
+        treasury: Pubkey,
+    },
+
+    /// Stop accepting `mint` as a listing currency
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The config admin, refunded the rent
+    /// 1. `[writable]` The accepted mint account
+    /// 2. `[]` The program config account (PDA of `["config"]`)
+    RemoveAcceptedMint {
+        mint: Pubkey,
+    },
+
+    /// Escrow several NFTs under one bundle sold for a single price
+    /// 
+    /// The pieces stay locked in their escrows until the bundle is bought
+    /// or cancelled. Bundles are priced in the marketplace payment token.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The seller's account
+    /// 1. `[writable]` The bundle account (PDA of `["bundle", seller, bundle_id]`)
+    /// 2. `[]` The token program
+    /// 3. `[]` The system program
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The program config account (PDA of `["config"]`)
+    /// 6. For each of 2 to `MAX_BUNDLE_SIZE` pieces: `[]` the NFT mint,
+    ///    `[writable]` the seller's NFT account, `[writable]` the escrow (PDA of
+    ///    `["bundle_escrow", bundle, nft_mint]`), `[writable]` the provenance account
+    ///    and `[]` the NFT's metadata account
+    ListBundle {
+        bundle_id: u64,
+        price: u64,
+    },
+
+    /// Buy every piece of a bundle in one transaction
+    /// 
+    /// The price is split evenly across the pieces and each share is paid out
+    /// according to that piece's provenance, as a single sale would be.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer's account
+    /// 1. `[writable]` The bundle account
+    /// 2. `[writable]` The buyer's payment token account
+    /// 3. `[writable]` The seller's account, refunded the rent
+    /// 4. `[writable]` The seller's payment token account
+    /// 5. `[writable]` The creator's payment token account
+    /// 6. `[writable]` The treasury's payment token account
+    /// 7. `[]` The marketplace config account
+    /// 8. `[]` The token program
+    /// 9. `[]` The program config account (PDA of `["config"]`)
+    /// 10. For each piece, in bundle order: `[writable]` the escrow, `[writable]`
+    ///     the buyer's NFT account, `[writable]` the original artist's payment token
+    ///     account (unused without a verified artist) and `[writable]` the provenance
+    ///     account, followed by the daily art
+    ///     account and artist token accounts if the piece is daily art
+    PurchaseBundle {
+        /// Most the buyer is willing to pay for the whole bundle
+        max_price: u64,
+    },
+
+    /// Return every piece of a bundle to the seller
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The seller's account
+    /// 1. `[writable]` The bundle account
+    /// 2. `[]` The token program
+    /// 3. `[]` The program config account (PDA of `["config"]`)
+    /// 4. For each piece, in bundle order: `[writable]` the escrow and
+    ///    `[writable]` the seller's NFT account
+    CancelBundle {},
+
+    /// Open a limited edition of a master artwork held by the artist
+    /// 
+    /// Buyers then mint numbered prints with `MintEditionPrint` until
+    /// `max_supply` is reached. Editions are priced in the marketplace
+    /// payment token. The master must be an NFT whose verified artist, per
+    /// its provenance or else its metadata, is the signer.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The artist's account
+    /// 1. `[writable]` The edition account (PDA of `["edition", master_mint]`)
+    /// 2. `[]` The master NFT mint
+    /// 3. `[]` The artist's master NFT account
+    /// 4. `[]` The system program
+    /// 5. `[]` The rent sysvar
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    /// 7. `[]` The master's provenance account (PDA of `["provenance", master_mint]`)
+    /// 8. `[]` The master's metadata account (PDA of `["metadata", metadata program, master_mint]`)
+    CreateEdition {
+        /// Primary price of one print
+        price: u64,
+        max_supply: u32,
+    },
+
+    /// Mint the next numbered print of an edition to the buyer
+    /// 
+    /// The price is paid through the primary split with the artist as
+    /// seller. The print gets its own provenance record naming the artist,
+    /// so it can be resold through `ListArtwork` like any other NFT.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer's account
+    /// 1. `[writable]` The edition account
+    /// 2. `[writable]` The print record (PDA of `["edition_print", edition, number]`)
+    /// 3. `[signer, writable]` The print NFT mint account (uninitialized)
+    /// 4. `[writable]` The buyer's associated token account for the print
+    /// 5. `[writable]` The buyer's payment token account
+    /// 6. `[writable]` The artist's payment token account
+    /// 7. `[writable]` The creator's payment token account
+    /// 8. `[writable]` The treasury's payment token account
+    /// 9. `[writable]` The artist's payment token account again, receiving the artist royalty
+    /// 10. `[writable]` The print's provenance account (PDA of `["provenance", print_mint]`)
+    /// 11. `[]` The marketplace config account
+    /// 12. `[]` The token program
+    /// 13. `[]` The associated token account program
+    /// 14. `[]` The system program
+    /// 15. `[]` The rent sysvar
+    /// 16. `[]` The program config account (PDA of `["config"]`)
+    MintEditionPrint {
+        /// Most the buyer is willing to pay for the print
+        max_price: u64,
+    },
+
+    /// Create a launchpad drop minting up to `max_supply` new NFTs in phases
+    /// 
+    /// Each phase runs from its start time until the next phase starts, with
+    /// its own price, optional allowlist and per-wallet cap. Drops are priced
+    /// in the marketplace payment token.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The creator's account
+    /// 1. `[writable]` The drop account (PDA of `["drop", creator, drop_id]`)
+    /// 2. `[]` The system program
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The program config account (PDA of `["config"]`)
+    CreateDrop {
+        drop_id: u64,
+        max_supply: u32,
+        /// Up to `MAX_DROP_PHASES` phases in strictly increasing start time order
+        phases: Vec<drop::DropPhase>,
+    },
+
+    /// Mint one NFT of a drop during its current phase
+    /// 
+    /// The price is paid through the primary split with the creator as
+    /// seller, and the NFT gets a provenance record naming the creator.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer's account
+    /// 1. `[writable]` The drop account
+    /// 2. `[writable]` The buyer's mint counter (PDA of `["drop_mint_counter", drop, phase, buyer]`)
+    /// 3. `[signer, writable]` The NFT mint account (uninitialized)
+    /// 4. `[writable]` The buyer's associated token account for the NFT
+    /// 5. `[writable]` The buyer's payment token account
+    /// 6. `[writable]` The creator's payment token account
+    /// 7. `[writable]` The Vortex creator fee payment token account
+    /// 8. `[writable]` The treasury's payment token account
+    /// 9. `[writable]` The creator's payment token account again, receiving the artist royalty
+    /// 10. `[writable]` The NFT's provenance account (PDA of `["provenance", nft_mint]`)
+    /// 11. `[]` The marketplace config account
+    /// 12. `[]` The token program
+    /// 13. `[]` The associated token account program
+    /// 14. `[]` The system program
+    /// 15. `[]` The rent sysvar
+    /// 16. `[]` The program config account (PDA of `["config"]`)
+    MintFromDrop {
+        /// Most the buyer is willing to pay
+        max_price: u64,
+        /// Merkle proof of the buyer's wallet, empty for public phases
+        proof: Vec<[u8; 32]>,
+    },
+}
+
+/// Fee recipients shared by every sale
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MarketplaceConfig {
+    pub creator: Pubkey,
+    pub treasury: Pubkey,
+    pub payment_mint: Pubkey,
+    pub bump: u8,
+}
+
+impl MarketplaceConfig {
+    pub const LEN: usize = 32 + 32 + 32 + 1;
+}
+
+/// How a listing is sold
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum ListingMode {
+    /// Bought outright at `price`
+    FixedPrice,
+    /// Sold to the highest bidder once the auction ends
+    Auction(Auction),
+    /// Bought outright at a price declining over time
+    Dutch(DutchAuction),
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -47,8 +670,125 @@ pub struct ArtworkListing {
     pub seller: Pubkey,
     pub nft_mint: Pubkey,
     pub price: u64,
+    /// Royalty advertised by the seller. Informational only: sales always
+    /// pay the fixed split of `royalty::get_royalty_breakdown`.
     pub royalty_percentage: u8,
     pub is_active: bool,
+    /// Token account holding the NFT while the listing is active
+    pub escrow: Pubkey,
+    pub bump: u8,
+    /// Bumped on every change so buyers can pin the version they saw
+    pub nonce: u64,
+    /// Unix timestamp from which the listing can no longer be bought
+    pub expires_at: Option<i64>,
+    /// Currency of the sale, `None` for the marketplace payment token
+    pub payment_mint: Option<Pubkey>,
+    pub mode: ListingMode,
+}
+
+impl ArtworkListing {
+    /// Size of the largest mode, so a listing account can be reused for any
+    pub const LEN: usize = 32 + 32 + 8 + 1 + 1 + 32 + 1 + 8 + 9 + 33 + 1 + Auction::LEN;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expires_at, Some(expires_at) if now >= expires_at)
+    }
+
+    /// Anyone may close the listing at `now`: it is sold, cancelled or
+    /// expired. Auctions end through `SettleAuction` instead.
+    pub fn is_closable(&self, now: i64) -> bool {
+        !self.is_active || (self.is_expired(now) && !matches!(self.mode, ListingMode::Auction(_)))
+    }
+
+    /// The listing is an auction with bids
+    pub fn has_bids(&self) -> bool {
+        matches!(&self.mode, ListingMode::Auction(auction) if auction.bid_count > 0)
+    }
+
+    /// Change the price, royalty and expiry of an active listing at `now`
+    /// and bump its nonce.
+    ///
+    /// The royalty is fixed while the listing has bids or `offers_open`,
+    /// see `offer::has_open_offers`.
+    ///
+    /// For auctions the price is the reserve, which is fixed once bidding
+    /// has started. Dutch listings follow their schedule and can't be
+    /// updated.
+    pub fn update(
+        &mut self,
+        new_price: u64,
+        royalty_percentage: Option<u8>,
+        expiry: Option<i64>,
+        offers_open: bool,
+        now: i64,
+    ) -> ProgramResult {
+        if !self.is_active {
+            return Err(VortexError::ListingInactive.into());
+        }
+        if matches!(expiry, Some(expiry) if expiry <= now) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if let Some(royalty_percentage) = royalty_percentage {
+            if royalty_percentage != self.royalty_percentage && (self.has_bids() || offers_open) {
+                return Err(VortexError::RoyaltyLocked.into());
+            }
+            self.royalty_percentage = royalty_percentage;
+        }
+        match &mut self.mode {
+            ListingMode::FixedPrice => {}
+            ListingMode::Auction(auction) => {
+                if expiry.is_some() {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                if auction.bid_count > 0 && new_price != auction.reserve {
+                    return Err(VortexError::InvalidState.into());
+                }
+                auction.reserve = new_price;
+            }
+            ListingMode::Dutch(_) => return Err(VortexError::InvalidState.into()),
+        }
+        self.price = new_price;
+        self.expires_at = expiry;
+        self.nonce = self.nonce.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Check that a purchase at `now` matches this listing and return the
+    /// price to charge
+    pub fn check_purchase(
+        &self,
+        seller: &Pubkey,
+        nft_mint: &Pubkey,
+        max_price: u64,
+        expected_nonce: u64,
+        now: i64,
+    ) -> Result<u64, ProgramError> {
+        if !self.is_active {
+            return Err(VortexError::ListingInactive.into());
+        }
+        if self.is_expired(now) {
+            return Err(VortexError::ListingExpired.into());
+        }
+        if self.seller != *seller || self.nft_mint != *nft_mint {
+            return Err(VortexError::ListingMismatch.into());
+        }
+        if let ListingMode::Auction(_) = self.mode {
+            return Err(VortexError::InvalidState.into());
+        }
+        if self.nonce != expected_nonce {
+            return Err(VortexError::StaleListing.into());
+        }
+        let price = current_price(self, now);
+        if price > max_price {
+            return Err(VortexError::PriceExceedsMaximum.into());
+        }
+        Ok(price)
+    }
+
+    /// Signer seeds of the listing PDA, which owns the escrow
+    pub fn signer_seeds<'a>(&'a self, bump: &'a [u8]) -> [&'a [u8]; 4] {
+        [LISTING_SEED, self.seller.as_ref(), self.nft_mint.as_ref(), bump]
+    }
 }
 
 // Entry point
@@ -62,38 +802,264 @@ pub fn process_instruction(
     let instruction = MarketplaceInstruction::try_from_slice(instruction_data)?;
 
     match instruction {
-        MarketplaceInstruction::ListArtwork { price, royalty_percentage } => {
+        MarketplaceInstruction::ListArtwork { price, royalty_percentage, expires_at, payment_mint } => {
             msg!("Instruction: List Artwork");
-            process_list_artwork(program_id, accounts, price, royalty_percentage)
+            process_list_artwork(program_id, accounts, price, royalty_percentage, expires_at, payment_mint)
         }
-        MarketplaceInstruction::PurchaseArtwork { price } => {
+        MarketplaceInstruction::PurchaseArtwork { max_price, expected_nonce } => {
             msg!("Instruction: Purchase Artwork");
-            process_purchase_artwork(program_id, accounts, price)
+            process_purchase_artwork(program_id, accounts, max_price, expected_nonce)
         }
         MarketplaceInstruction::CancelListing {} => {
             msg!("Instruction: Cancel Listing");
             process_cancel_listing(program_id, accounts)
         }
+        MarketplaceInstruction::InitializeMarketplace { creator, treasury, payment_mint } => {
+            msg!("Instruction: Initialize Marketplace");
+            process_initialize_marketplace(program_id, accounts, creator, treasury, payment_mint)
+        }
+        MarketplaceInstruction::MintDailyArt { date, artists } => {
+            msg!("Instruction: Mint Daily Art");
+            daily_art::process_mint_daily_art(program_id, accounts, date, artists)
+        }
+        MarketplaceInstruction::AddParticipatingArtist { date, artist } => {
+            msg!("Instruction: Add Participating Artist");
+            daily_art::process_add_participating_artist(program_id, accounts, date, artist)
+        }
+        MarketplaceInstruction::CreateAuction { reserve, min_increment_bps, end_time, extension_window } => {
+            msg!("Instruction: Create Auction");
+            auction::process_create_auction(
+                program_id,
+                accounts,
+                reserve,
+                min_increment_bps,
+                end_time,
+                extension_window,
+            )
+        }
+        MarketplaceInstruction::PlaceBid { amount } => {
+            msg!("Instruction: Place Bid");
+            auction::process_place_bid(program_id, accounts, amount)
+        }
+        MarketplaceInstruction::SettleAuction {} => {
+            msg!("Instruction: Settle Auction");
+            auction::process_settle_auction(program_id, accounts)
+        }
+        MarketplaceInstruction::CreateDutchAuction { start_price, end_price, start_time, end_time } => {
+            msg!("Instruction: Create Dutch Auction");
+            dutch::process_create_dutch_auction(program_id, accounts, start_price, end_price, start_time, end_time)
+        }
+        MarketplaceInstruction::MakeOffer { nft_mint, amount, expiry } => {
+            msg!("Instruction: Make Offer");
+            offer::process_make_offer(program_id, accounts, nft_mint, amount, expiry)
+        }
+        MarketplaceInstruction::AcceptOffer {} => {
+            msg!("Instruction: Accept Offer");
+            offer::process_accept_offer(program_id, accounts)
+        }
+        MarketplaceInstruction::CancelOffer {} => {
+            msg!("Instruction: Cancel Offer");
+            offer::process_cancel_offer(program_id, accounts)
+        }
+        MarketplaceInstruction::MakeCollectionOffer { collection, price, quantity, expiry } => {
+            msg!("Instruction: Make Collection Offer");
+            collection_offer::process_make_collection_offer(program_id, accounts, collection, price, quantity, expiry)
+        }
+        MarketplaceInstruction::AcceptCollectionOffer {} => {
+            msg!("Instruction: Accept Collection Offer");
+            collection_offer::process_accept_collection_offer(program_id, accounts)
+        }
+        MarketplaceInstruction::CancelCollectionOffer {} => {
+            msg!("Instruction: Cancel Collection Offer");
+            collection_offer::process_cancel_collection_offer(program_id, accounts)
+        }
+        MarketplaceInstruction::UpdateListing { new_price, royalty_percentage, expiry } => {
+            msg!("Instruction: Update Listing");
+            process_update_listing(program_id, accounts, new_price, royalty_percentage, expiry)
+        }
+        MarketplaceInstruction::CloseExpiredListing {} => {
+            msg!("Instruction: Close Expired Listing");
+            process_close_expired_listing(program_id, accounts)
+        }
+        MarketplaceInstruction::AddAcceptedMint { mint, treasury } => {
+            msg!("Instruction: Add Accepted Mint");
+            currency::process_add_accepted_mint(program_id, accounts, mint, treasury)
+        }
+        MarketplaceInstruction::RemoveAcceptedMint { mint } => {
+            msg!("Instruction: Remove Accepted Mint");
+            currency::process_remove_accepted_mint(program_id, accounts, mint)
+        }
+        MarketplaceInstruction::ListBundle { bundle_id, price } => {
+            msg!("Instruction: List Bundle");
+            bundle::process_list_bundle(program_id, accounts, bundle_id, price)
+        }
+        MarketplaceInstruction::PurchaseBundle { max_price } => {
+            msg!("Instruction: Purchase Bundle");
+            bundle::process_purchase_bundle(program_id, accounts, max_price)
+        }
+        MarketplaceInstruction::CancelBundle {} => {
+            msg!("Instruction: Cancel Bundle");
+            bundle::process_cancel_bundle(program_id, accounts)
+        }
+        MarketplaceInstruction::CreateEdition { price, max_supply } => {
+            msg!("Instruction: Create Edition");
+            edition::process_create_edition(program_id, accounts, price, max_supply)
+        }
+        MarketplaceInstruction::MintEditionPrint { max_price } => {
+            msg!("Instruction: Mint Edition Print");
+            edition::process_mint_edition_print(program_id, accounts, max_price)
+        }
+        MarketplaceInstruction::CreateDrop { drop_id, max_supply, phases } => {
+            msg!("Instruction: Create Drop");
+            drop::process_create_drop(program_id, accounts, drop_id, max_supply, phases)
+        }
+        MarketplaceInstruction::MintFromDrop { max_price, proof } => {
+            msg!("Instruction: Mint From Drop");
+            drop::process_mint_from_drop(program_id, accounts, max_price, proof)
+        }
     }
 }
 
-fn process_list_artwork(
+// Load a listing PDA owned by this program
+fn load_listing(program_id: &Pubkey, listing_account: &AccountInfo) -> Result<ArtworkListing, ProgramError> {
+    if listing_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(ArtworkListing::deserialize(&mut &listing_account.data.borrow()[..])?)
+}
+
+/// Accounts shared by every instruction that opens a listing
+pub struct ListingAccounts<'a, 'b> {
+    pub seller: &'b AccountInfo<'a>,
+    pub listing_account: &'b AccountInfo<'a>,
+    pub nft_mint: &'b AccountInfo<'a>,
+    pub seller_nft_account: &'b AccountInfo<'a>,
+    pub escrow_account: &'b AccountInfo<'a>,
+    pub provenance_account: &'b AccountInfo<'a>,
+    pub metadata_account: &'b AccountInfo<'a>,
+    pub token_program: &'b AccountInfo<'a>,
+    pub system_program: &'b AccountInfo<'a>,
+    pub rent_account: &'b AccountInfo<'a>,
+}
+
+impl<'a, 'b> ListingAccounts<'a, 'b> {
+    pub fn next<I: Iterator<Item = &'b AccountInfo<'a>>>(iter: &mut I) -> Result<Self, ProgramError> {
+        Ok(Self {
+            seller: next_account_info(iter)?,
+            listing_account: next_account_info(iter)?,
+            nft_mint: next_account_info(iter)?,
+            seller_nft_account: next_account_info(iter)?,
+            escrow_account: next_account_info(iter)?,
+            provenance_account: next_account_info(iter)?,
+            metadata_account: next_account_info(iter)?,
+            token_program: next_account_info(iter)?,
+            system_program: next_account_info(iter)?,
+            rent_account: next_account_info(iter)?,
+        })
+    }
+}
+
+// Escrow the seller's NFT and write an active listing in `mode`
+fn open_listing(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
+    accounts: &ListingAccounts,
     price: u64,
     royalty_percentage: u8,
+    expires_at: Option<i64>,
+    payment_mint: Option<Pubkey>,
+    mode: ListingMode,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    let seller = next_account_info(account_info_iter)?;
-    let listing_account = next_account_info(account_info_iter)?;
-    let nft_mint = next_account_info(account_info_iter)?;
-    let seller_nft_account = next_account_info(account_info_iter)?;
+    let ListingAccounts {
+        seller,
+        listing_account,
+        nft_mint,
+        seller_nft_account,
+        escrow_account,
+        provenance_account,
+        metadata_account,
+        token_program,
+        system_program,
+        rent_account,
+    } = *accounts;
 
     // Verify seller is signer
     if !seller.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let seller_nft = TokenAccount::unpack(&seller_nft_account.data.borrow())?;
+    if seller_nft.owner != *seller.key || seller_nft.mint != *nft_mint.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if seller_nft.amount < 1 {
+        return Err(VortexError::InsufficientFunds.into());
+    }
+
+    let (listing_key, listing_bump) = Pubkey::find_program_address(
+        &[LISTING_SEED, seller.key.as_ref(), nft_mint.key.as_ref()],
+        program_id,
+    );
+    if *listing_account.key != listing_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (escrow_key, escrow_bump) = Pubkey::find_program_address(
+        &[ESCROW_SEED, seller.key.as_ref(), nft_mint.key.as_ref()],
+        program_id,
+    );
+    if *escrow_account.key != escrow_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    let provenance = load_or_create_provenance(
+        program_id,
+        provenance_account,
+        metadata_account,
+        nft_mint.key,
+        seller,
+        system_program,
+        &rent,
+    )?;
+
+    // A cancelled or sold listing account is reused when relisting; a
+    // closed one is recreated past the nonces it had
+    let nonce = if listing_account.data_is_empty() {
+        create_pda_account(
+            seller,
+            listing_account,
+            system_program,
+            program_id,
+            &rent,
+            ArtworkListing::LEN,
+            &[LISTING_SEED, seller.key.as_ref(), nft_mint.key.as_ref(), &[listing_bump]],
+        )?;
+        provenance.listing_nonce
+    } else {
+        let previous = load_listing(program_id, listing_account)?;
+        if previous.is_active {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        previous.nonce.wrapping_add(1)
+    };
+
+    create_pda_token_account(
+        seller,
+        escrow_account,
+        nft_mint,
+        &listing_key,
+        system_program,
+        token_program,
+        &rent,
+        &[ESCROW_SEED, seller.key.as_ref(), nft_mint.key.as_ref(), &[escrow_bump]],
+    )?;
+
+    // Move the NFT into escrow
+    transfer_tokens(token_program, seller_nft_account, escrow_account, seller, 1, &[])?;
+
     // Create listing
     let listing = ArtworkListing {
         seller: *seller.key,
@@ -101,31 +1067,153 @@ fn process_list_artwork(
         price,
         royalty_percentage,
         is_active: true,
+        escrow: escrow_key,
+        bump: listing_bump,
+        nonce,
+        expires_at,
+        payment_mint,
+        mode,
     };
 
     listing.serialize(&mut *listing_account.data.borrow_mut())?;
+
     Ok(())
 }
 
-fn process_purchase_artwork(
+fn process_list_artwork(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     price: u64,
+    royalty_percentage: u8,
+    expires_at: Option<i64>,
+    payment_mint: Option<Pubkey>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let listing_accounts = ListingAccounts::next(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    if matches!(expires_at, Some(expires_at) if expires_at <= Clock::get()?.unix_timestamp) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if let Some(payment_mint) = payment_mint {
+        load_accepted_mint(program_id, next_account_info(account_info_iter)?, &payment_mint)?;
+    }
+
+    open_listing(
+        program_id,
+        &listing_accounts,
+        price,
+        royalty_percentage,
+        expires_at,
+        payment_mint,
+        ListingMode::FixedPrice,
+    )?;
+
+    emit(VortexEvent::ArtworkListed {
+        seller: *listing_accounts.seller.key,
+        nft_mint: *listing_accounts.nft_mint.key,
+        price,
+        royalty_percentage,
+    });
+
+    Ok(())
+}
+
+fn process_purchase_artwork(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_price: u64,
+    expected_nonce: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let buyer = next_account_info(account_info_iter)?;
     let listing_account = next_account_info(account_info_iter)?;
     let seller_token_account = next_account_info(account_info_iter)?;
     let buyer_token_account = next_account_info(account_info_iter)?;
-    let nft_account = next_account_info(account_info_iter)?;
+    let buyer_nft_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let creator_token_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let artist_token_account = next_account_info(account_info_iter)?;
+    let seller = next_account_info(account_info_iter)?;
+    let provenance_account = next_account_info(account_info_iter)?;
+    let marketplace_config = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let sale = SaleAccounts {
+        seller_token_account,
+        creator_token_account,
+        treasury_token_account,
+        artist_token_account,
+        provenance_account,
+        marketplace_config,
+    };
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
 
     // Verify buyer is signer
     if !buyer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Transfer tokens and NFT
-    // Implementation details to be added
+    let mut listing = load_listing(program_id, listing_account)?;
+    let buyer_nft = TokenAccount::unpack(&buyer_nft_account.data.borrow())?;
+    let now = Clock::get()?.unix_timestamp;
+    let price = listing.check_purchase(seller.key, &buyer_nft.mint, max_price, expected_nonce, now)?;
+    if listing.escrow != *escrow_account.key {
+        return Err(VortexError::ListingMismatch.into());
+    }
+
+    // Pay everyone out of the buyer's account, or wallet for SOL
+    let source = match listing.payment_mint {
+        Some(mint) if is_native_sol(&mint) => buyer,
+        _ => buyer_token_account,
+    };
+    let breakdown = settle_sale(
+        program_id,
+        &sale,
+        account_info_iter,
+        &listing.nft_mint,
+        &listing.seller,
+        price,
+        listing.payment_mint,
+        source,
+        buyer,
+        token_program,
+        &[],
+    )?;
+
+    // Release the NFT and close the escrow
+    let bump = [listing.bump];
+    let listing_seeds = listing.signer_seeds(&bump);
+    transfer_tokens(
+        token_program,
+        escrow_account,
+        buyer_nft_account,
+        listing_account,
+        1,
+        &[&listing_seeds],
+    )?;
+    close_token_account(token_program, escrow_account, seller, listing_account, &[&listing_seeds])?;
+
+    listing.is_active = false;
+    listing.nonce = listing.nonce.wrapping_add(1);
+    listing.serialize(&mut *listing_account.data.borrow_mut())?;
+
+    emit(VortexEvent::ArtworkPurchased {
+        buyer: *buyer.key,
+        seller: listing.seller,
+        nft_mint: listing.nft_mint,
+        price,
+        creator_fee: breakdown.creator_fee,
+        artist_royalty: breakdown.artist_royalty,
+        treasury_fee: breakdown.treasury_fee,
+        seller_proceeds: breakdown.seller_proceeds,
+    });
 
     Ok(())
 }
@@ -137,19 +1225,782 @@ fn process_cancel_listing(
     let account_info_iter = &mut accounts.iter();
     let seller = next_account_info(account_info_iter)?;
     let listing_account = next_account_info(account_info_iter)?;
+    let seller_nft_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
 
     // Verify seller is signer
     if !seller.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
     // Deactivate listing
-    let mut listing = ArtworkListing::try_from_slice(&listing_account.data.borrow())?;
+    let mut listing = load_listing(program_id, listing_account)?;
     if listing.seller != *seller.key {
         return Err(ProgramError::InvalidAccountData);
     }
+    if !listing.is_active {
+        return Err(VortexError::ListingInactive.into());
+    }
+    if listing.escrow != *escrow_account.key {
+        return Err(VortexError::ListingMismatch.into());
+    }
+
+    // Bids can't be taken back, so an auction is only cancellable before the first
+    let bid_vault = match &listing.mode {
+        ListingMode::FixedPrice | ListingMode::Dutch(_) => None,
+        ListingMode::Auction(auction) => {
+            if auction.bid_count > 0 {
+                return Err(VortexError::InvalidState.into());
+            }
+            let bid_vault = next_account_info(account_info_iter)?;
+            if *bid_vault.key != auction.bid_vault {
+                return Err(VortexError::ListingMismatch.into());
+            }
+            Some(bid_vault)
+        }
+    };
+
+    // Return the NFT and close the escrow
+    let bump = [listing.bump];
+    let listing_seeds = listing.signer_seeds(&bump);
+    transfer_tokens(
+        token_program,
+        escrow_account,
+        seller_nft_account,
+        listing_account,
+        1,
+        &[&listing_seeds],
+    )?;
+    close_token_account(token_program, escrow_account, seller, listing_account, &[&listing_seeds])?;
+    if let Some(bid_vault) = bid_vault {
+        close_token_account(token_program, bid_vault, seller, listing_account, &[&listing_seeds])?;
+    }
+
     listing.is_active = false;
+    listing.nonce = listing.nonce.wrapping_add(1);
     listing.serialize(&mut *listing_account.data.borrow_mut())?;
 
+    emit(VortexEvent::ListingCancelled {
+        seller: *seller.key,
+        nft_mint: listing.nft_mint,
+    });
+
     Ok(())
-} 
\ No newline at end of file
+}
+
+fn process_close_expired_listing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let caller = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let seller = next_account_info(account_info_iter)?;
+    let seller_nft_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let provenance_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify caller is signer
+    if !caller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let listing = load_listing(program_id, listing_account)?;
+    if listing.seller != *seller.key {
+        return Err(VortexError::ListingMismatch.into());
+    }
+    if !listing.is_closable(Clock::get()?.unix_timestamp) {
+        return Err(VortexError::InvalidState.into());
+    }
+
+    // An expired listing still holds the NFT
+    if listing.is_active {
+        if listing.escrow != *escrow_account.key {
+            return Err(VortexError::ListingMismatch.into());
+        }
+        check_token_account(seller_nft_account, &listing.seller, &listing.nft_mint)?;
+
+        let bump = [listing.bump];
+        let listing_seeds = listing.signer_seeds(&bump);
+        transfer_tokens(
+            token_program,
+            escrow_account,
+            seller_nft_account,
+            listing_account,
+            1,
+            &[&listing_seeds],
+        )?;
+        close_token_account(token_program, escrow_account, seller, listing_account, &[&listing_seeds])?;
+    }
+
+    // The recreated listing account must not start over at nonce 0
+    let (provenance_key, _) =
+        Pubkey::find_program_address(&[provenance::PROVENANCE_SEED, listing.nft_mint.as_ref()], program_id);
+    if *provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut provenance = provenance::load_provenance(program_id, provenance_account, &listing.nft_mint)?;
+    provenance.retire_listing_nonce(listing.nonce);
+    provenance.serialize(&mut *provenance_account.data.borrow_mut())?;
+
+    close_program_account(listing_account, seller)?;
+
+    emit(VortexEvent::ListingClosed {
+        seller: listing.seller,
+        nft_mint: listing.nft_mint,
+        closed_by: *caller.key,
+    });
+
+    Ok(())
+}
+
+fn process_update_listing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_price: u64,
+    royalty_percentage: Option<u8>,
+    expiry: Option<i64>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let seller = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify seller is signer
+    if !seller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut listing = load_listing(program_id, listing_account)?;
+    if listing.seller != *seller.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    // Only a royalty change needs the open offers
+    let offers_open = match royalty_percentage {
+        Some(royalty_percentage) if royalty_percentage != listing.royalty_percentage => {
+            offer::has_open_offers(program_id, account_info_iter, &listing.nft_mint)?
+        }
+        _ => false,
+    };
+
+    listing.update(new_price, royalty_percentage, expiry, offers_open, Clock::get()?.unix_timestamp)?;
+    listing.serialize(&mut *listing_account.data.borrow_mut())?;
+
+    emit(VortexEvent::ListingUpdated {
+        seller: listing.seller,
+        nft_mint: listing.nft_mint,
+        price: listing.price,
+        royalty_percentage: listing.royalty_percentage,
+        expires_at: listing.expires_at,
+        nonce: listing.nonce,
+    });
+
+    Ok(())
+}
+
+fn process_initialize_marketplace(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    creator: Pubkey,
+    treasury: Pubkey,
+    payment_mint: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let marketplace_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Listing)?;
+
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if load_config(program_config)?.admin != *admin.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let (config_key, config_bump) =
+        Pubkey::find_program_address(&[MARKETPLACE_CONFIG_SEED], program_id);
+    if *marketplace_config.key != config_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !marketplace_config.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        admin,
+        marketplace_config,
+        system_program,
+        program_id,
+        &rent,
+        MarketplaceConfig::LEN,
+        &[MARKETPLACE_CONFIG_SEED, &[config_bump]],
+    )?;
+
+    let config = MarketplaceConfig {
+        creator,
+        treasury,
+        payment_mint,
+        bump: config_bump,
+    };
+    config.serialize(&mut *marketplace_config.data.borrow_mut())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::system_program;
+    use crate::test_utils::{self, balance, empty, initialized_config, mint, program, rent_sysvar, run, token_account, wallet};
+
+    fn listing() -> ArtworkListing {
+        ArtworkListing {
+            seller: Pubkey::new_unique(),
+            nft_mint: Pubkey::new_unique(),
+            price: 1_000,
+            royalty_percentage: 10,
+            is_active: true,
+            escrow: Pubkey::new_unique(),
+            bump: 255,
+            nonce: 3,
+            expires_at: None,
+            payment_mint: None,
+            mode: ListingMode::FixedPrice,
+        }
+    }
+
+    fn auction() -> Auction {
+        Auction {
+            reserve: 1_000,
+            min_increment_bps: 500,
+            end_time: 0,
+            extension_window: 0,
+            highest_bid: 0,
+            highest_bidder: Pubkey::default(),
+            bid_count: 0,
+            bid_vault: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_listing_len() {
+        let mut listing = listing();
+        listing.expires_at = Some(0);
+        listing.payment_mint = Some(Pubkey::new_unique());
+        listing.mode = ListingMode::Auction(auction());
+        assert_eq!(listing.try_to_vec().unwrap().len(), ArtworkListing::LEN);
+    }
+
+    #[test]
+    fn test_check_purchase() {
+        let listing = listing();
+        let (seller, mint) = (listing.seller, listing.nft_mint);
+
+        assert_eq!(listing.check_purchase(&seller, &mint, 1_000, 3, 0), Ok(1_000));
+        assert_eq!(listing.check_purchase(&seller, &mint, 5_000, 3, 0), Ok(1_000));
+        assert_eq!(
+            listing.check_purchase(&seller, &mint, 999, 3, 0),
+            Err(VortexError::PriceExceedsMaximum.into())
+        );
+        assert_eq!(
+            listing.check_purchase(&seller, &mint, 1_000, 2, 0),
+            Err(VortexError::StaleListing.into())
+        );
+        assert_eq!(
+            listing.check_purchase(&Pubkey::new_unique(), &mint, 1_000, 3, 0),
+            Err(VortexError::ListingMismatch.into())
+        );
+        assert_eq!(
+            listing.check_purchase(&seller, &Pubkey::new_unique(), 1_000, 3, 0),
+            Err(VortexError::ListingMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_auction_cannot_be_bought_outright() {
+        let mut listing = listing();
+        listing.mode = ListingMode::Auction(auction());
+        let (seller, mint) = (listing.seller, listing.nft_mint);
+        assert_eq!(
+            listing.check_purchase(&seller, &mint, 1_000, 3, 0),
+            Err(VortexError::InvalidState.into())
+        );
+    }
+
+    #[test]
+    fn test_dutch_listing_charges_current_price() {
+        let mut listing = listing();
+        listing.mode = ListingMode::Dutch(DutchAuction {
+            start_price: 10_000,
+            end_price: 2_000,
+            start_time: 0,
+            end_time: 1_000,
+        });
+        let (seller, mint) = (listing.seller, listing.nft_mint);
+        assert_eq!(
+            listing.check_purchase(&seller, &mint, 5_000, 3, 0),
+            Err(VortexError::PriceExceedsMaximum.into())
+        );
+        assert_eq!(listing.check_purchase(&seller, &mint, 6_000, 3, 500), Ok(6_000));
+        assert_eq!(listing.check_purchase(&seller, &mint, 6_000, 3, 5_000), Ok(2_000));
+    }
+
+    #[test]
+    fn test_inactive_listing_cannot_be_bought() {
+        let mut listing = listing();
+        listing.is_active = false;
+        let (seller, mint) = (listing.seller, listing.nft_mint);
+        assert_eq!(
+            listing.check_purchase(&seller, &mint, 1_000, 3, 0),
+            Err(VortexError::ListingInactive.into())
+        );
+    }
+
+    #[test]
+    fn test_expired_listing_cannot_be_bought() {
+        let mut listing = listing();
+        listing.expires_at = Some(100);
+        let (seller, mint) = (listing.seller, listing.nft_mint);
+        assert_eq!(listing.check_purchase(&seller, &mint, 1_000, 3, 99), Ok(1_000));
+        assert_eq!(
+            listing.check_purchase(&seller, &mint, 1_000, 3, 100),
+            Err(VortexError::ListingExpired.into())
+        );
+    }
+
+    #[test]
+    fn test_closable_once_expired_or_inactive() {
+        let mut listing = listing();
+        assert!(!listing.is_closable(i64::MAX));
+
+        listing.expires_at = Some(100);
+        assert!(!listing.is_closable(99));
+        assert!(listing.is_closable(100));
+
+        listing.expires_at = None;
+        listing.is_active = false;
+        assert!(listing.is_closable(0));
+    }
+
+    #[test]
+    fn test_auction_is_settled_not_closed() {
+        let mut listing = listing();
+        listing.expires_at = Some(100);
+        listing.mode = ListingMode::Auction(auction());
+        assert!(!listing.is_closable(100));
+    }
+
+    #[test]
+    fn test_update_bumps_nonce() {
+        let mut listing = listing();
+        listing.update(2_000, Some(15), Some(500), false, 0).unwrap();
+        assert_eq!(listing.price, 2_000);
+        assert_eq!(listing.royalty_percentage, 15);
+        assert_eq!(listing.expires_at, Some(500));
+        assert_eq!(listing.nonce, 4);
+
+        listing.update(1_500, None, None, false, 0).unwrap();
+        assert_eq!(listing.royalty_percentage, 15);
+        assert_eq!(listing.expires_at, None);
+        assert_eq!(listing.nonce, 5);
+
+        assert_eq!(listing.update(1_500, None, Some(0), false, 0), Err(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn test_royalty_locked_by_offers_and_bids() {
+        let mut listing = listing();
+        assert_eq!(listing.update(2_000, Some(20), None, true, 0), Err(VortexError::RoyaltyLocked.into()));
+        listing.update(2_000, Some(10), None, true, 0).unwrap();
+        listing.update(2_000, Some(20), None, false, 0).unwrap();
+
+        let mut listing = self::listing();
+        let mut auction = auction();
+        auction.bid_count = 1;
+        auction.highest_bid = 1_000;
+        listing.mode = ListingMode::Auction(auction);
+        assert_eq!(listing.update(1_000, Some(20), None, false, 0), Err(VortexError::RoyaltyLocked.into()));
+        assert_eq!(listing.update(500, None, None, false, 0), Err(VortexError::InvalidState.into()));
+    }
+
+    /// A marketplace initialized through the dispatcher
+    struct Market {
+        program_id: Pubkey,
+        program_config: AccountInfo<'static>,
+        marketplace_config: AccountInfo<'static>,
+        payment_mint: AccountInfo<'static>,
+        creator: Pubkey,
+        treasury: Pubkey,
+    }
+
+    impl Market {
+        fn new() -> Self {
+            test_utils::install();
+            let program_id = Pubkey::new_unique();
+            let admin = wallet();
+            let market = Market {
+                program_id,
+                program_config: initialized_config(&admin, &Pubkey::new_unique()),
+                marketplace_config: empty(Pubkey::find_program_address(&[MARKETPLACE_CONFIG_SEED], &program_id).0),
+                payment_mint: mint(admin.key),
+                creator: Pubkey::new_unique(),
+                treasury: Pubkey::new_unique(),
+            };
+            let data = MarketplaceInstruction::InitializeMarketplace {
+                creator: market.creator,
+                treasury: market.treasury,
+                payment_mint: *market.payment_mint.key,
+            }
+            .try_to_vec()
+            .unwrap();
+            let accounts = [
+                admin.clone(),
+                market.marketplace_config.clone(),
+                program(system_program::id()),
+                rent_sysvar(),
+                market.program_config.clone(),
+            ];
+            run(process_instruction, &program_id, &accounts, &data).unwrap();
+            market
+        }
+
+        fn provenance(&self, nft_mint: &Pubkey) -> AccountInfo<'static> {
+            empty(Pubkey::find_program_address(&[provenance::PROVENANCE_SEED, nft_mint.as_ref()], &self.program_id).0)
+        }
+
+        // List the NFT at 1_000, returning the listing and its escrow
+        fn list(
+            &self,
+            seller: &AccountInfo<'static>,
+            nft_mint: &AccountInfo<'static>,
+            seller_nft_account: &AccountInfo<'static>,
+            provenance_account: &AccountInfo<'static>,
+            metadata_account: &AccountInfo<'static>,
+        ) -> (AccountInfo<'static>, AccountInfo<'static>) {
+            let seeds = [seller.key.as_ref(), nft_mint.key.as_ref()];
+            let listing_account =
+                empty(Pubkey::find_program_address(&[LISTING_SEED, seeds[0], seeds[1]], &self.program_id).0);
+            let escrow_account =
+                empty(Pubkey::find_program_address(&[ESCROW_SEED, seeds[0], seeds[1]], &self.program_id).0);
+            let data = MarketplaceInstruction::ListArtwork {
+                price: 1_000,
+                royalty_percentage: 10,
+                expires_at: None,
+                payment_mint: None,
+            }
+            .try_to_vec()
+            .unwrap();
+            let accounts = [
+                seller.clone(),
+                listing_account.clone(),
+                nft_mint.clone(),
+                seller_nft_account.clone(),
+                escrow_account.clone(),
+                provenance_account.clone(),
+                metadata_account.clone(),
+                program(spl_token::id()),
+                program(system_program::id()),
+                rent_sysvar(),
+                self.program_config.clone(),
+            ];
+            run(process_instruction, &self.program_id, &accounts, &data).unwrap();
+            assert_eq!(balance(&escrow_account), 1);
+            (listing_account, escrow_account)
+        }
+
+        // Buy a listing at 1_000, returning what the creator, treasury,
+        // artist and seller received
+        fn purchase(
+            &self,
+            seller: &AccountInfo<'static>,
+            artist: &Pubkey,
+            nft_mint: &Pubkey,
+            listing_account: &AccountInfo<'static>,
+            escrow_account: &AccountInfo<'static>,
+            provenance_account: &AccountInfo<'static>,
+        ) -> [u64; 4] {
+            let buyer = wallet();
+            let payment_mint = self.payment_mint.key;
+            let buyer_token_account = token_account(payment_mint, buyer.key, 1_000);
+            let buyer_nft_account = token_account(nft_mint, buyer.key, 0);
+            let seller_token_account = token_account(payment_mint, seller.key, 0);
+            let artist_token_account = token_account(payment_mint, artist, 0);
+            let creator_token_account = token_account(payment_mint, &self.creator, 0);
+            let treasury_token_account = token_account(payment_mint, &self.treasury, 0);
+            let expected_nonce = load_listing(&self.program_id, listing_account).unwrap().nonce;
+            let data = MarketplaceInstruction::PurchaseArtwork { max_price: 1_000, expected_nonce }
+                .try_to_vec()
+                .unwrap();
+            let accounts = [
+                buyer.clone(),
+                listing_account.clone(),
+                seller_token_account.clone(),
+                buyer_token_account.clone(),
+                buyer_nft_account.clone(),
+                escrow_account.clone(),
+                creator_token_account.clone(),
+                treasury_token_account.clone(),
+                artist_token_account.clone(),
+                seller.clone(),
+                provenance_account.clone(),
+                self.marketplace_config.clone(),
+                program(spl_token::id()),
+                self.program_config.clone(),
+            ];
+            run(process_instruction, &self.program_id, &accounts, &data).unwrap();
+
+            assert_eq!(balance(&buyer_token_account), 0);
+            assert_eq!(balance(&buyer_nft_account), 1);
+            assert!(!load_listing(&self.program_id, listing_account).unwrap().is_active);
+            [
+                balance(&creator_token_account),
+                balance(&treasury_token_account),
+                balance(&artist_token_account),
+                balance(&seller_token_account),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_initialize_marketplace_then_purchase_through_dispatcher() {
+        let market = Market::new();
+        let artist = wallet();
+        let nft_mint = mint(artist.key);
+        let artist_nft_account = token_account(nft_mint.key, artist.key, 1);
+        let provenance_account = market.provenance(nft_mint.key);
+        let metadata_account = test_utils::metadata(nft_mint.key, artist.key);
+        let (listing_account, escrow_account) =
+            market.list(&artist, &nft_mint, &artist_nft_account, &provenance_account, &metadata_account);
+
+        // The primary sale splits 5% / 15% / 80%
+        let paid =
+            market.purchase(&artist, artist.key, nft_mint.key, &listing_account, &escrow_account, &provenance_account);
+        assert_eq!(paid, [50, 150, 800, 0]);
+    }
+
+    #[test]
+    fn test_next_holder_after_cancelled_listing_pays_resale_split() {
+        let market = Market::new();
+        let artist = wallet();
+        let nft_mint = mint(artist.key);
+        let artist_nft_account = token_account(nft_mint.key, artist.key, 1);
+        let provenance_account = market.provenance(nft_mint.key);
+        let metadata_account = test_utils::metadata(nft_mint.key, artist.key);
+        let (listing_account, escrow_account) =
+            market.list(&artist, &nft_mint, &artist_nft_account, &provenance_account, &metadata_account);
+
+        let accounts = [
+            artist.clone(),
+            listing_account.clone(),
+            artist_nft_account.clone(),
+            escrow_account.clone(),
+            program(spl_token::id()),
+            market.program_config.clone(),
+        ];
+        let data = MarketplaceInstruction::CancelListing {}.try_to_vec().unwrap();
+        run(process_instruction, &market.program_id, &accounts, &data).unwrap();
+
+        // The NFT changes hands off the marketplace, so its next sale is a resale
+        let holder = wallet();
+        let holder_nft_account = token_account(nft_mint.key, holder.key, 1);
+        let (listing_account, escrow_account) =
+            market.list(&holder, &nft_mint, &holder_nft_account, &provenance_account, &metadata_account);
+        let paid =
+            market.purchase(&holder, artist.key, nft_mint.key, &listing_account, &escrow_account, &provenance_account);
+        assert_eq!(paid, [50, 150, 150, 650]);
+    }
+
+    #[test]
+    fn test_closed_listing_nonces_stay_retired_on_relisting() {
+        let market = Market::new();
+        let artist = wallet();
+        let nft_mint = mint(artist.key);
+        let artist_nft_account = token_account(nft_mint.key, artist.key, 1);
+        let provenance_account = market.provenance(nft_mint.key);
+        let metadata_account = test_utils::metadata(nft_mint.key, artist.key);
+        let (listing_account, escrow_account) =
+            market.list(&artist, &nft_mint, &artist_nft_account, &provenance_account, &metadata_account);
+        assert_eq!(load_listing(&market.program_id, &listing_account).unwrap().nonce, 0);
+
+        let accounts = [
+            artist.clone(),
+            listing_account.clone(),
+            artist_nft_account.clone(),
+            escrow_account.clone(),
+            program(spl_token::id()),
+            market.program_config.clone(),
+        ];
+        let data = MarketplaceInstruction::CancelListing {}.try_to_vec().unwrap();
+        run(process_instruction, &market.program_id, &accounts, &data).unwrap();
+
+        let accounts = [
+            wallet(),
+            listing_account.clone(),
+            artist.clone(),
+            artist_nft_account.clone(),
+            escrow_account.clone(),
+            program(spl_token::id()),
+            market.program_config.clone(),
+            provenance_account.clone(),
+        ];
+        let data = MarketplaceInstruction::CloseExpiredListing {}.try_to_vec().unwrap();
+        run(process_instruction, &market.program_id, &accounts, &data).unwrap();
+
+        // The runtime drops the closed account, so relisting recreates it
+        let (listing_account, escrow_account) =
+            market.list(&artist, &nft_mint, &artist_nft_account, &provenance_account, &metadata_account);
+        let listing = load_listing(&market.program_id, &listing_account).unwrap();
+        assert_eq!(listing.nonce, 2);
+        for stale_nonce in [0, 1] {
+            assert_eq!(
+                listing.check_purchase(artist.key, nft_mint.key, 1_000, stale_nonce, test_utils::NOW),
+                Err(VortexError::StaleListing.into())
+            );
+        }
+        let paid =
+            market.purchase(&artist, artist.key, nft_mint.key, &listing_account, &escrow_account, &provenance_account);
+        assert_eq!(paid, [50, 150, 800, 0]);
+    }
+
+    #[test]
+    fn test_externally_minted_nft_is_not_attributed_to_its_lister() {
+        let market = Market::new();
+        let collector = wallet();
+        let nft_mint = mint(&Pubkey::new_unique());
+        let collector_nft_account = token_account(nft_mint.key, collector.key, 1);
+
+        // Without a verified creator the treasury keeps the artist royalty
+        let provenance_account = market.provenance(nft_mint.key);
+        let metadata_account = empty(metadata::find_metadata_address(nft_mint.key).0);
+        let (listing_account, escrow_account) =
+            market.list(&collector, &nft_mint, &collector_nft_account, &provenance_account, &metadata_account);
+        let provenance = provenance::load_provenance(&market.program_id, &provenance_account, nft_mint.key).unwrap();
+        assert!(!provenance.has_artist());
+        let paid = market.purchase(
+            &collector,
+            collector.key,
+            nft_mint.key,
+            &listing_account,
+            &escrow_account,
+            &provenance_account,
+        );
+        assert_eq!(paid, [50, 300, 0, 650]);
+
+        // With one, the verified creator is the artist, not the first lister
+        let artist = Pubkey::new_unique();
+        let nft_mint = mint(&artist);
+        let collector_nft_account = token_account(nft_mint.key, collector.key, 1);
+        let provenance_account = market.provenance(nft_mint.key);
+        let metadata_account = test_utils::metadata(nft_mint.key, &artist);
+        let (listing_account, escrow_account) =
+            market.list(&collector, &nft_mint, &collector_nft_account, &provenance_account, &metadata_account);
+        let paid =
+            market.purchase(&collector, &artist, nft_mint.key, &listing_account, &escrow_account, &provenance_account);
+        assert_eq!(paid, [50, 150, 150, 650]);
+    }
+
+    #[test]
+    fn test_open_offer_locks_royalty_until_cancelled() {
+        let market = Market::new();
+        let artist = wallet();
+        let nft_mint = mint(artist.key);
+        let artist_nft_account = token_account(nft_mint.key, artist.key, 1);
+        let provenance_account = market.provenance(nft_mint.key);
+        let metadata_account = empty(metadata::find_metadata_address(nft_mint.key).0);
+        let (listing_account, _) =
+            market.list(&artist, &nft_mint, &artist_nft_account, &provenance_account, &metadata_account);
+
+        let tally_account = empty(offer::find_offer_tally_address(&market.program_id, nft_mint.key).0);
+        let update_royalty = |royalty_percentage: u8| {
+            let accounts = [
+                artist.clone(),
+                listing_account.clone(),
+                market.program_config.clone(),
+                tally_account.clone(),
+                metadata_account.clone(),
+            ];
+            let data = MarketplaceInstruction::UpdateListing {
+                new_price: 1_000,
+                royalty_percentage: Some(royalty_percentage),
+                expiry: None,
+            }
+            .try_to_vec()
+            .unwrap();
+            run(process_instruction, &market.program_id, &accounts, &data)
+        };
+
+        let buyer = wallet();
+        let buyer_token_account = token_account(market.payment_mint.key, buyer.key, 1);
+        let offer_key = offer::find_offer_address(&market.program_id, nft_mint.key, buyer.key).0;
+        let offer_account = empty(offer_key);
+        let vault = empty(
+            Pubkey::find_program_address(&[offer::OFFER_VAULT_SEED, offer_key.as_ref()], &market.program_id).0,
+        );
+        let data = MarketplaceInstruction::MakeOffer { nft_mint: *nft_mint.key, amount: 1, expiry: test_utils::NOW + 1 }
+            .try_to_vec()
+            .unwrap();
+        let accounts = [
+            buyer.clone(),
+            offer_account.clone(),
+            buyer_token_account.clone(),
+            vault.clone(),
+            market.payment_mint.clone(),
+            market.marketplace_config.clone(),
+            program(spl_token::id()),
+            program(system_program::id()),
+            rent_sysvar(),
+            market.program_config.clone(),
+            tally_account.clone(),
+        ];
+        run(process_instruction, &market.program_id, &accounts, &data).unwrap();
+        assert_eq!(update_royalty(20).unwrap_err(), VortexError::RoyaltyLocked.into());
+
+        let accounts = [
+            buyer.clone(),
+            offer_account.clone(),
+            vault.clone(),
+            buyer_token_account.clone(),
+            buyer.clone(),
+            program(spl_token::id()),
+            market.program_config.clone(),
+            tally_account.clone(),
+        ];
+        let data = MarketplaceInstruction::CancelOffer {}.try_to_vec().unwrap();
+        run(process_instruction, &market.program_id, &accounts, &data).unwrap();
+        assert_eq!(balance(&buyer_token_account), 1);
+
+        // Closing the offer lifts the lock
+        update_royalty(20).unwrap();
+        assert_eq!(load_listing(&market.program_id, &listing_account).unwrap().royalty_percentage, 20);
+    }
+}
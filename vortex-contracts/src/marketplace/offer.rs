@@ -0,0 +1,474 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token::state::Account as TokenAccount;
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::marketplace::metadata::verified_collection;
+use crate::marketplace::provenance::load_or_create_provenance;
+use crate::marketplace::settlement::{check_token_account, load_marketplace_config, settle_sale, SaleAccounts};
+use crate::utils::{close_program_account, close_token_account, create_pda_account, create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+/// Seed of an offer PDA, followed by the NFT mint and the buyer
+pub const OFFER_SEED: &[u8] = b"offer";
+/// Seed of an offer's payment vault, followed by the offer
+pub const OFFER_VAULT_SEED: &[u8] = b"offer_vault";
+/// Seed of an offer tally, followed by the NFT mint or the collection mint
+pub const OFFER_TALLY_SEED: &[u8] = b"offer_tally";
+
+/// A standing offer on an NFT, funded up front
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Offer {
+    pub buyer: Pubkey,
+    pub nft_mint: Pubkey,
+    pub amount: u64,
+    /// Unix timestamp from which the offer can no longer be accepted
+    pub expiry: i64,
+    /// Token account holding `amount`, owned by the offer
+    pub vault: Pubkey,
+    pub bump: u8,
+}
+
+impl Offer {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 32 + 1;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expiry
+    }
+
+    /// Signer seeds of the offer PDA, which owns the vault
+    pub fn signer_seeds<'a>(&'a self, bump: &'a [u8]) -> [&'a [u8]; 4] {
+        [OFFER_SEED, self.nft_mint.as_ref(), self.buyer.as_ref(), bump]
+    }
+}
+
+/// Open offers on an NFT, or collection offers on a collection, which lock
+/// the royalty of its listings, see `has_open_offers`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct OfferTally {
+    /// The NFT mint, or the collection mint for collection offers
+    pub mint: Pubkey,
+    pub open_offers: u32,
+    pub bump: u8,
+}
+
+impl OfferTally {
+    pub const LEN: usize = 32 + 4 + 1;
+}
+
+/// Address of the offer tally of `mint`
+pub fn find_offer_tally_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OFFER_TALLY_SEED, mint.as_ref()], program_id)
+}
+
+/// Offers open on `mint`, none before its tally exists
+pub fn open_offers(program_id: &Pubkey, tally_account: &AccountInfo, mint: &Pubkey) -> Result<u32, ProgramError> {
+    if *tally_account.key != find_offer_tally_address(program_id, mint).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if tally_account.data_is_empty() {
+        return Ok(0);
+    }
+    if tally_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(OfferTally::try_from_slice(&tally_account.data.borrow())?.open_offers)
+}
+
+/// Count a new offer on `mint`, creating its tally on the first one
+pub fn count_offer<'a>(
+    program_id: &Pubkey,
+    tally_account: &AccountInfo<'a>,
+    mint: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> ProgramResult {
+    let (tally_key, tally_bump) = find_offer_tally_address(program_id, mint);
+    if *tally_account.key != tally_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut tally = if tally_account.data_is_empty() {
+        create_pda_account(
+            payer,
+            tally_account,
+            system_program,
+            program_id,
+            rent,
+            OfferTally::LEN,
+            &[OFFER_TALLY_SEED, mint.as_ref(), &[tally_bump]],
+        )?;
+        OfferTally { mint: *mint, open_offers: 0, bump: tally_bump }
+    } else {
+        if tally_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        OfferTally::try_from_slice(&tally_account.data.borrow())?
+    };
+    tally.open_offers = tally.open_offers.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+    tally.serialize(&mut *tally_account.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Remove a closed offer on `mint` from its tally
+pub fn uncount_offer(program_id: &Pubkey, tally_account: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    if *tally_account.key != find_offer_tally_address(program_id, mint).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if tally_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut tally = OfferTally::try_from_slice(&tally_account.data.borrow())?;
+    tally.open_offers = tally.open_offers.checked_sub(1).ok_or(VortexError::InvalidState)?;
+    tally.serialize(&mut *tally_account.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Whether offers are open on `nft_mint` or, for a verified collection
+/// member, collection offers on its collection.
+///
+/// Reads the NFT's offer tally and metadata account from `accounts`, then
+/// the collection's offer tally for collection members.
+pub fn has_open_offers<'a: 'b, 'b, I: Iterator<Item = &'b AccountInfo<'a>>>(
+    program_id: &Pubkey,
+    accounts: &mut I,
+    nft_mint: &Pubkey,
+) -> Result<bool, ProgramError> {
+    let nft_tally_account = next_account_info(accounts)?;
+    let metadata_account = next_account_info(accounts)?;
+
+    if open_offers(program_id, nft_tally_account, nft_mint)? > 0 {
+        return Ok(true);
+    }
+    match verified_collection(metadata_account, nft_mint)? {
+        Some(collection) => {
+            let collection_tally_account = next_account_info(accounts)?;
+            Ok(open_offers(program_id, collection_tally_account, &collection)? > 0)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Address of `buyer`'s offer on `nft_mint`
+pub fn find_offer_address(program_id: &Pubkey, nft_mint: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OFFER_SEED, nft_mint.as_ref(), buyer.as_ref()], program_id)
+}
+
+// Load an offer PDA owned by this program
+fn load_offer(program_id: &Pubkey, offer_account: &AccountInfo) -> Result<Offer, ProgramError> {
+    if offer_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(Offer::try_from_slice(&offer_account.data.borrow())?)
+}
+
+// Escrow `amount` in a new offer on `nft_mint`
+pub fn process_make_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    nft_mint: Pubkey,
+    amount: u64,
+    expiry: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let buyer = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let payment_mint = next_account_info(account_info_iter)?;
+    let marketplace_config = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let tally_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify buyer is signer
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if amount == 0 || expiry <= Clock::get()?.unix_timestamp {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let config = load_marketplace_config(program_id, marketplace_config)?;
+    if *payment_mint.key != config.payment_mint {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    let (offer_key, offer_bump) = find_offer_address(program_id, &nft_mint, buyer.key);
+    if *offer_account.key != offer_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !offer_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let (vault_key, vault_bump) =
+        Pubkey::find_program_address(&[OFFER_VAULT_SEED, offer_key.as_ref()], program_id);
+    if *vault.key != vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        buyer,
+        offer_account,
+        system_program,
+        program_id,
+        &rent,
+        Offer::LEN,
+        &[OFFER_SEED, nft_mint.as_ref(), buyer.key.as_ref(), &[offer_bump]],
+    )?;
+    create_pda_token_account(
+        buyer,
+        vault,
+        payment_mint,
+        &offer_key,
+        system_program,
+        token_program,
+        &rent,
+        &[OFFER_VAULT_SEED, offer_key.as_ref(), &[vault_bump]],
+    )?;
+
+    transfer_tokens(token_program, buyer_token_account, vault, buyer, amount, &[])?;
+
+    let offer = Offer {
+        buyer: *buyer.key,
+        nft_mint,
+        amount,
+        expiry,
+        vault: vault_key,
+        bump: offer_bump,
+    };
+    offer.serialize(&mut *offer_account.data.borrow_mut())?;
+
+    // The open offer fixes the royalty of the NFT's listings
+    count_offer(program_id, tally_account, &nft_mint, buyer, system_program, &rent)?;
+
+    emit(VortexEvent::OfferMade {
+        buyer: *buyer.key,
+        nft_mint,
+        amount,
+        expiry,
+    });
+
+    Ok(())
+}
+
+// Sell the NFT to an offer: pay out of its vault and hand the NFT over
+pub fn process_accept_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let seller = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let seller_nft_account = next_account_info(account_info_iter)?;
+    let buyer_nft_account = next_account_info(account_info_iter)?;
+    let buyer = next_account_info(account_info_iter)?;
+    let sale = SaleAccounts::next(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let tally_account = next_account_info(account_info_iter)?;
+    let metadata_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify seller is signer
+    if !seller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let offer = load_offer(program_id, offer_account)?;
+    if offer.vault != *vault.key || offer.buyer != *buyer.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(VortexError::OfferExpired.into());
+    }
+
+    let seller_nft = TokenAccount::unpack(&seller_nft_account.data.borrow())?;
+    if seller_nft.owner != *seller.key || seller_nft.mint != offer.nft_mint || seller_nft.amount < 1 {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    check_token_account(buyer_nft_account, &offer.buyer, &offer.nft_mint)?;
+
+    // An NFT that was never listed is attributed to its verified creator
+    let rent = Rent::from_account_info(rent_account)?;
+    load_or_create_provenance(
+        program_id,
+        sale.provenance_account,
+        metadata_account,
+        &offer.nft_mint,
+        seller,
+        system_program,
+        &rent,
+    )?;
+
+    let bump = [offer.bump];
+    let offer_seeds = offer.signer_seeds(&bump);
+    let breakdown = settle_sale(
+        program_id,
+        &sale,
+        account_info_iter,
+        &offer.nft_mint,
+        seller.key,
+        offer.amount,
+        None,
+        vault,
+        offer_account,
+        token_program,
+        &[&offer_seeds],
+    )?;
+
+    transfer_tokens(token_program, seller_nft_account, buyer_nft_account, seller, 1, &[])?;
+
+    // Return the rent of the vault and offer to the buyer
+    close_token_account(token_program, vault, buyer, offer_account, &[&offer_seeds])?;
+    close_program_account(offer_account, buyer)?;
+    uncount_offer(program_id, tally_account, &offer.nft_mint)?;
+
+    emit(VortexEvent::ArtworkPurchased {
+        buyer: offer.buyer,
+        seller: *seller.key,
+        nft_mint: offer.nft_mint,
+        price: offer.amount,
+        creator_fee: breakdown.creator_fee,
+        artist_royalty: breakdown.artist_royalty,
+        treasury_fee: breakdown.treasury_fee,
+        seller_proceeds: breakdown.seller_proceeds,
+    });
+    emit(VortexEvent::OfferAccepted {
+        buyer: offer.buyer,
+        seller: *seller.key,
+        nft_mint: offer.nft_mint,
+        amount: offer.amount,
+    });
+
+    Ok(())
+}
+
+// Refund an offer; only its buyer may do so before it expires
+pub fn process_cancel_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let caller = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let buyer = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let tally_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Purchase)?;
+
+    // Verify caller is signer
+    if !caller.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let offer = load_offer(program_id, offer_account)?;
+    if offer.vault != *vault.key || offer.buyer != *buyer.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if *caller.key != offer.buyer && !offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let payment_mint = TokenAccount::unpack(&vault.data.borrow())?.mint;
+    check_token_account(buyer_token_account, &offer.buyer, &payment_mint)?;
+
+    // Everything goes back to the buyer, whoever calls
+    let bump = [offer.bump];
+    let offer_seeds = offer.signer_seeds(&bump);
+    transfer_tokens(
+        token_program,
+        vault,
+        buyer_token_account,
+        offer_account,
+        offer.amount,
+        &[&offer_seeds],
+    )?;
+    close_token_account(token_program, vault, buyer, offer_account, &[&offer_seeds])?;
+    close_program_account(offer_account, buyer)?;
+    uncount_offer(program_id, tally_account, &offer.nft_mint)?;
+
+    emit(VortexEvent::OfferCancelled {
+        buyer: offer.buyer,
+        nft_mint: offer.nft_mint,
+        amount: offer.amount,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer() -> Offer {
+        Offer {
+            buyer: Pubkey::new_unique(),
+            nft_mint: Pubkey::new_unique(),
+            amount: 5_000,
+            expiry: 1_000,
+            vault: Pubkey::new_unique(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_offer_len() {
+        assert_eq!(offer().try_to_vec().unwrap().len(), Offer::LEN);
+        let tally = OfferTally {
+            mint: Pubkey::new_unique(),
+            open_offers: 1,
+            bump: 255,
+        };
+        assert_eq!(tally.try_to_vec().unwrap().len(), OfferTally::LEN);
+    }
+
+    #[test]
+    fn test_offer_expires_at_expiry() {
+        let offer = offer();
+        assert!(!offer.is_expired(999));
+        assert!(offer.is_expired(1_000));
+    }
+
+    #[test]
+    fn test_offer_address_is_per_buyer() {
+        let program_id = Pubkey::new_unique();
+        let offer = offer();
+        let (address, _) = find_offer_address(&program_id, &offer.nft_mint, &offer.buyer);
+        assert_ne!(address, find_offer_address(&program_id, &offer.nft_mint, &Pubkey::new_unique()).0);
+    }
+}
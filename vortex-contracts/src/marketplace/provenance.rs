@@ -0,0 +1,191 @@
+use solana_program::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::marketplace::metadata::verified_artist;
+use crate::utils::create_pda_account;
+use crate::VortexError;
+
+/// Seed of an NFT's provenance record, followed by the NFT mint
+pub const PROVENANCE_SEED: &[u8] = b"provenance";
+
+/// Sale history of an NFT traded on the marketplace
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Provenance {
+    pub nft_mint: Pubkey,
+    /// Receives the resale royalty; `Pubkey::default()` when no artist was
+    /// verified, in which case the treasury takes the royalty
+    pub original_artist: Pubkey,
+    pub sale_count: u64,
+    /// Nonce a newly created listing account of the NFT starts from, past
+    /// every nonce of the listings closed so far
+    pub listing_nonce: u64,
+    pub bump: u8,
+    /// Set for daily art, whose sales are shared by its participating artists
+    pub daily_art: Option<Pubkey>,
+}
+
+impl Provenance {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 1 + 32;
+
+    /// A sale by `seller` is the primary sale: the NFT has never sold and
+    /// the original artist is selling it. Anyone else's first sale, say after
+    /// the artist listed, cancelled and transferred the NFT, is a resale.
+    pub fn is_primary(&self, seller: &Pubkey) -> bool {
+        self.sale_count == 0 && self.has_artist() && self.original_artist == *seller
+    }
+
+    /// Retire the nonces of a listing being closed, up to `nonce`, so that
+    /// purchases signed against them stay invalid once the NFT is relisted
+    pub fn retire_listing_nonce(&mut self, nonce: u64) {
+        self.listing_nonce = self.listing_nonce.max(nonce.saturating_add(1));
+    }
+
+    /// An artist was verified when the record was created
+    pub fn has_artist(&self) -> bool {
+        self.original_artist != Pubkey::default()
+    }
+}
+
+/// Load the provenance record of `nft_mint`, creating it if the NFT has never
+/// been listed.
+///
+/// The original artist of an NFT minted elsewhere is its verified creator in
+/// `metadata_account`, never whoever lists or sells it first. Without one the
+/// NFT has no artist.
+pub fn load_or_create_provenance<'a>(
+    program_id: &Pubkey,
+    provenance_account: &AccountInfo<'a>,
+    metadata_account: &AccountInfo<'a>,
+    nft_mint: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> Result<Provenance, ProgramError> {
+    let (provenance_key, provenance_bump) =
+        Pubkey::find_program_address(&[PROVENANCE_SEED, nft_mint.as_ref()], program_id);
+    if *provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !provenance_account.data_is_empty() {
+        return load_provenance(program_id, provenance_account, nft_mint);
+    }
+
+    let artist = verified_artist(metadata_account, nft_mint)?.unwrap_or_default();
+    create_provenance(
+        program_id,
+        provenance_account,
+        provenance_bump,
+        nft_mint,
+        &artist,
+        None,
+        payer,
+        system_program,
+        rent,
+    )
+}
+
+/// `artist` is the verified artist of `nft_mint`: its original artist when
+/// the NFT already has provenance, its verified creator otherwise
+pub fn is_verified_artist(
+    program_id: &Pubkey,
+    provenance_account: &AccountInfo,
+    metadata_account: &AccountInfo,
+    nft_mint: &Pubkey,
+    artist: &Pubkey,
+) -> Result<bool, ProgramError> {
+    let (provenance_key, _) = Pubkey::find_program_address(&[PROVENANCE_SEED, nft_mint.as_ref()], program_id);
+    if *provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !provenance_account.data_is_empty() {
+        let provenance = load_provenance(program_id, provenance_account, nft_mint)?;
+        return Ok(provenance.has_artist() && provenance.original_artist == *artist);
+    }
+    Ok(verified_artist(metadata_account, nft_mint)? == Some(*artist))
+}
+
+/// Create the provenance record of a new NFT at its verified PDA
+#[allow(clippy::too_many_arguments)]
+pub fn create_provenance<'a>(
+    program_id: &Pubkey,
+    provenance_account: &AccountInfo<'a>,
+    provenance_bump: u8,
+    nft_mint: &Pubkey,
+    original_artist: &Pubkey,
+    daily_art: Option<Pubkey>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> Result<Provenance, ProgramError> {
+    create_pda_account(
+        payer,
+        provenance_account,
+        system_program,
+        program_id,
+        rent,
+        Provenance::LEN,
+        &[PROVENANCE_SEED, nft_mint.as_ref(), &[provenance_bump]],
+    )?;
+
+    let provenance = Provenance {
+        nft_mint: *nft_mint,
+        original_artist: *original_artist,
+        sale_count: 0,
+        listing_nonce: 0,
+        bump: provenance_bump,
+        daily_art,
+    };
+    provenance.serialize(&mut *provenance_account.data.borrow_mut())?;
+
+    Ok(provenance)
+}
+
+/// Load an existing provenance record of `nft_mint`
+pub fn load_provenance(
+    program_id: &Pubkey,
+    provenance_account: &AccountInfo,
+    nft_mint: &Pubkey,
+) -> Result<Provenance, ProgramError> {
+    if provenance_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let provenance = Provenance::deserialize(&mut &provenance_account.data.borrow()[..])?;
+    if provenance.nft_mint != *nft_mint {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    Ok(provenance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_artist_makes_the_primary_sale() {
+        let artist = Pubkey::new_unique();
+        let mut provenance = Provenance {
+            nft_mint: Pubkey::new_unique(),
+            original_artist: artist,
+            sale_count: 0,
+            listing_nonce: 0,
+            bump: 255,
+            daily_art: None,
+        };
+        assert!(provenance.is_primary(&artist));
+        assert!(!provenance.is_primary(&Pubkey::new_unique()));
+
+        provenance.sale_count = 1;
+        assert!(!provenance.is_primary(&artist));
+
+        // Without a verified artist no sale is primary
+        provenance.sale_count = 0;
+        provenance.original_artist = Pubkey::default();
+        assert!(!provenance.has_artist());
+        assert!(!provenance.is_primary(&Pubkey::default()));
+    }
+}
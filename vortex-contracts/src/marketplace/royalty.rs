@@ -0,0 +1,163 @@
+/// Basis points making up the whole sale price
+pub const TOTAL_BPS: u64 = 10_000;
+
+/// Share of the Vortex creator on every sale
+pub const CREATOR_BPS: u64 = 500;
+/// Primary sale: share of the artist; the treasury keeps the rest
+pub const PRIMARY_ARTIST_BPS: u64 = 8_000;
+/// Resale: royalty of the original artist
+pub const RESALE_ARTIST_BPS: u64 = 1_500;
+/// Resale: share of the current owner; the treasury keeps the rest
+pub const RESALE_SELLER_BPS: u64 = 6_500;
+
+/// Daily art, first sale: pool shared by the participating artists
+pub const DAILY_PRIMARY_ARTISTS_BPS: u64 = 9_500;
+/// Daily art, resale: pool shared by the participating artists
+pub const DAILY_RESALE_ARTISTS_BPS: u64 = 1_500;
+/// Daily art, resale: share of the current owner
+pub const DAILY_RESALE_SELLER_BPS: u64 = 8_000;
+
+/// How the price of a sale is paid out
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RoyaltyBreakdown {
+    pub creator_fee: u64,
+    pub artist_royalty: u64,
+    pub treasury_fee: u64,
+    pub seller_proceeds: u64,
+}
+
+// Share of `price` rounded down
+fn share(price: u64, bps: u64) -> u64 {
+    (price as u128 * bps as u128 / TOTAL_BPS as u128) as u64
+}
+
+/// Split `price` the way `TOLAArtDailyRoyalty.getRoyaltyBreakdown` does.
+///
+/// A primary sale pays 5% to the creator, 15% to the treasury and 80% to the
+/// artist. A resale pays 5% to the creator, 15% to the original artist, 15%
+/// to the treasury and 65% to the seller. Every share is rounded down and the
+/// rounding dust goes to the treasury, so the parts always add up to `price`.
+pub fn get_royalty_breakdown(price: u64, is_primary: bool) -> RoyaltyBreakdown {
+    let creator_fee = share(price, CREATOR_BPS);
+    let (artist_royalty, seller_proceeds) = if is_primary {
+        (share(price, PRIMARY_ARTIST_BPS), 0)
+    } else {
+        (share(price, RESALE_ARTIST_BPS), share(price, RESALE_SELLER_BPS))
+    };
+    RoyaltyBreakdown {
+        creator_fee,
+        artist_royalty,
+        treasury_fee: price - creator_fee - artist_royalty - seller_proceeds,
+        seller_proceeds,
+    }
+}
+
+/// Split the price of a daily art piece like `TOLAArtDailyRoyalty.processSale`.
+///
+/// The first sale pays 5% to the creator and 95% to the participating
+/// artists; a resale pays 5% to the creator, 15% to the artists and 80% to
+/// the seller. `artist_royalty` is the pool shared by all artists, see
+/// `split_evenly`. Rounding dust goes to the treasury.
+pub fn get_daily_art_breakdown(price: u64, is_primary: bool) -> RoyaltyBreakdown {
+    let creator_fee = share(price, CREATOR_BPS);
+    let (artist_royalty, seller_proceeds) = if is_primary {
+        (share(price, DAILY_PRIMARY_ARTISTS_BPS), 0)
+    } else {
+        (share(price, DAILY_RESALE_ARTISTS_BPS), share(price, DAILY_RESALE_SELLER_BPS))
+    };
+    RoyaltyBreakdown {
+        creator_fee,
+        artist_royalty,
+        treasury_fee: price - creator_fee - artist_royalty - seller_proceeds,
+        seller_proceeds,
+    }
+}
+
+/// Split `pool` into `count` equal shares, the last one taking the remainder
+pub fn split_evenly(pool: u64, count: usize) -> Vec<u64> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let per_share = pool / count as u64;
+    let mut shares = vec![per_share; count];
+    shares[count - 1] = pool - per_share * (count as u64 - 1);
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(breakdown: &RoyaltyBreakdown) -> u128 {
+        breakdown.creator_fee as u128
+            + breakdown.artist_royalty as u128
+            + breakdown.treasury_fee as u128
+            + breakdown.seller_proceeds as u128
+    }
+
+    #[test]
+    fn test_primary_split() {
+        let breakdown = get_royalty_breakdown(1_000_000, true);
+        assert_eq!(breakdown.creator_fee, 50_000);
+        assert_eq!(breakdown.treasury_fee, 150_000);
+        assert_eq!(breakdown.artist_royalty, 800_000);
+        assert_eq!(breakdown.seller_proceeds, 0);
+    }
+
+    #[test]
+    fn test_secondary_split() {
+        let breakdown = get_royalty_breakdown(1_000_000, false);
+        assert_eq!(breakdown.creator_fee, 50_000);
+        assert_eq!(breakdown.artist_royalty, 150_000);
+        assert_eq!(breakdown.treasury_fee, 150_000);
+        assert_eq!(breakdown.seller_proceeds, 650_000);
+    }
+
+    #[test]
+    fn test_dust_goes_to_treasury() {
+        // 5% of 999 is 49.95 and 80% is 799.2
+        let breakdown = get_royalty_breakdown(999, true);
+        assert_eq!(breakdown.creator_fee, 49);
+        assert_eq!(breakdown.artist_royalty, 799);
+        assert_eq!(breakdown.treasury_fee, 151);
+
+        // 15% of 999 is 149.85 and 65% is 649.35
+        let breakdown = get_royalty_breakdown(999, false);
+        assert_eq!(breakdown.artist_royalty, 149);
+        assert_eq!(breakdown.seller_proceeds, 649);
+        assert_eq!(breakdown.treasury_fee, 152);
+    }
+
+    #[test]
+    fn test_split_adds_up() {
+        for price in [0, 1, 19, 333, 10_001, u64::MAX] {
+            assert_eq!(total(&get_royalty_breakdown(price, true)), price as u128);
+            assert_eq!(total(&get_royalty_breakdown(price, false)), price as u128);
+            assert_eq!(total(&get_daily_art_breakdown(price, true)), price as u128);
+            assert_eq!(total(&get_daily_art_breakdown(price, false)), price as u128);
+        }
+    }
+
+    #[test]
+    fn test_daily_art_split() {
+        let breakdown = get_daily_art_breakdown(1_000_000, true);
+        assert_eq!(breakdown.creator_fee, 50_000);
+        assert_eq!(breakdown.artist_royalty, 950_000);
+        assert_eq!(breakdown.treasury_fee, 0);
+        assert_eq!(breakdown.seller_proceeds, 0);
+
+        let breakdown = get_daily_art_breakdown(1_000_000, false);
+        assert_eq!(breakdown.creator_fee, 50_000);
+        assert_eq!(breakdown.artist_royalty, 150_000);
+        assert_eq!(breakdown.treasury_fee, 0);
+        assert_eq!(breakdown.seller_proceeds, 800_000);
+    }
+
+    #[test]
+    fn test_split_evenly_gives_remainder_to_last() {
+        assert_eq!(split_evenly(100, 3), vec![33, 33, 34]);
+        assert_eq!(split_evenly(100, 1), vec![100]);
+        assert_eq!(split_evenly(2, 3), vec![0, 0, 2]);
+        assert!(split_evenly(100, 0).is_empty());
+    }
+}
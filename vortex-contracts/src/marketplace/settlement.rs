@@ -0,0 +1,191 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token::state::Account as TokenAccount;
+use crate::marketplace::currency::{is_native_sol, load_accepted_mint};
+use crate::marketplace::daily_art::load_daily_art;
+use crate::marketplace::provenance::{load_provenance, PROVENANCE_SEED};
+use crate::marketplace::royalty::{get_daily_art_breakdown, get_royalty_breakdown, split_evenly, RoyaltyBreakdown};
+use crate::marketplace::{MarketplaceConfig, MARKETPLACE_CONFIG_SEED};
+use crate::utils::{transfer_lamports, transfer_tokens};
+use crate::VortexError;
+
+/// Load the marketplace config PDA
+pub fn load_marketplace_config(
+    program_id: &Pubkey,
+    marketplace_config: &AccountInfo,
+) -> Result<MarketplaceConfig, ProgramError> {
+    let (config_key, _) = Pubkey::find_program_address(&[MARKETPLACE_CONFIG_SEED], program_id);
+    if *marketplace_config.key != config_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if marketplace_config.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(MarketplaceConfig::try_from_slice(&marketplace_config.data.borrow())?)
+}
+
+/// Verify a token account belongs to `owner` and holds `mint`
+pub fn check_token_account(token_account: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> ProgramResult {
+    let account = TokenAccount::unpack(&token_account.data.borrow())?;
+    if account.owner != *owner || account.mint != *mint {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    Ok(())
+}
+
+// Verify a payee account: the wallet itself for SOL, its token account otherwise
+fn check_payee(account: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> ProgramResult {
+    if is_native_sol(mint) {
+        if account.key != owner {
+            return Err(VortexError::InvalidAccount.into());
+        }
+        return Ok(());
+    }
+    check_token_account(account, owner, mint)
+}
+
+/// Accounts paid by a sale, passed in this order by every instruction that
+/// sells an NFT
+pub struct SaleAccounts<'a, 'b> {
+    pub seller_token_account: &'b AccountInfo<'a>,
+    pub creator_token_account: &'b AccountInfo<'a>,
+    pub treasury_token_account: &'b AccountInfo<'a>,
+    /// Unused for daily art, whose artists follow the fixed accounts, and for
+    /// NFTs without a verified artist
+    pub artist_token_account: &'b AccountInfo<'a>,
+    pub provenance_account: &'b AccountInfo<'a>,
+    pub marketplace_config: &'b AccountInfo<'a>,
+}
+
+impl<'a, 'b> SaleAccounts<'a, 'b> {
+    pub fn next<I: Iterator<Item = &'b AccountInfo<'a>>>(iter: &mut I) -> Result<Self, ProgramError> {
+        Ok(Self {
+            seller_token_account: next_account_info(iter)?,
+            creator_token_account: next_account_info(iter)?,
+            treasury_token_account: next_account_info(iter)?,
+            artist_token_account: next_account_info(iter)?,
+            provenance_account: next_account_info(iter)?,
+            marketplace_config: next_account_info(iter)?,
+        })
+    }
+}
+
+/// Pay `price` out of `source` through the royalty split and count the sale
+/// in the NFT's provenance.
+///
+/// `payment_mint` is `None` for the marketplace payment token. Any other
+/// mint is checked against its accepted mint account, read first from
+/// `remaining_accounts`, whose treasury receives the treasury fee. Native SOL
+/// is paid in lamports from the `source` wallet to the payees' wallets
+/// through the system program, read next.
+///
+/// Daily art pays its participating artists through the daily art account
+/// and one payee account per artist read from `remaining_accounts`.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_sale<'a, 'b, I: Iterator<Item = &'b AccountInfo<'a>>>(
+    program_id: &Pubkey,
+    sale: &SaleAccounts<'a, 'b>,
+    remaining_accounts: &mut I,
+    nft_mint: &Pubkey,
+    seller: &Pubkey,
+    price: u64,
+    payment_mint: Option<Pubkey>,
+    source: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<RoyaltyBreakdown, ProgramError> {
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (provenance_key, _) =
+        Pubkey::find_program_address(&[PROVENANCE_SEED, nft_mint.as_ref()], program_id);
+    if *sale.provenance_account.key != provenance_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut provenance = load_provenance(program_id, sale.provenance_account, nft_mint)?;
+
+    let config = load_marketplace_config(program_id, sale.marketplace_config)?;
+    let (mint, treasury) = match payment_mint {
+        Some(mint) if mint != config.payment_mint => {
+            let accepted_mint_account = next_account_info(remaining_accounts)?;
+            let accepted_mint = load_accepted_mint(program_id, accepted_mint_account, &mint)?;
+            (mint, accepted_mint.treasury)
+        }
+        _ => (config.payment_mint, config.treasury),
+    };
+    let system_program_account = if is_native_sol(&mint) {
+        let system_program_account = next_account_info(remaining_accounts)?;
+        if *system_program_account.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Some(system_program_account)
+    } else {
+        None
+    };
+
+    check_payee(sale.seller_token_account, seller, &mint)?;
+    check_payee(sale.creator_token_account, &config.creator, &mint)?;
+    check_payee(sale.treasury_token_account, &treasury, &mint)?;
+
+    let is_primary = provenance.is_primary(seller);
+    let breakdown = match provenance.daily_art {
+        Some(_) => get_daily_art_breakdown(price, is_primary),
+        None => get_royalty_breakdown(price, is_primary),
+    };
+    let mut payments = vec![
+        (sale.creator_token_account, breakdown.creator_fee),
+        (sale.treasury_token_account, breakdown.treasury_fee),
+        (sale.seller_token_account, breakdown.seller_proceeds),
+    ];
+
+    // The artist royalty goes to the original artist or is shared by the
+    // daily artists; the treasury keeps it when no artist was verified
+    match provenance.daily_art {
+        None if !provenance.has_artist() => {
+            payments.push((sale.treasury_token_account, breakdown.artist_royalty));
+        }
+        None => {
+            check_payee(sale.artist_token_account, &provenance.original_artist, &mint)?;
+            payments.push((sale.artist_token_account, breakdown.artist_royalty));
+        }
+        Some(daily_art_key) => {
+            let daily_art_account = next_account_info(remaining_accounts)?;
+            if *daily_art_account.key != daily_art_key {
+                return Err(VortexError::InvalidAccount.into());
+            }
+            let daily_art = load_daily_art(program_id, daily_art_account)?;
+            let shares = split_evenly(breakdown.artist_royalty, daily_art.artists.len());
+            for (artist, amount) in daily_art.artists.iter().zip(shares) {
+                let daily_artist_token_account = next_account_info(remaining_accounts)?;
+                check_payee(daily_artist_token_account, artist, &mint)?;
+                payments.push((daily_artist_token_account, amount));
+            }
+        }
+    }
+
+    for (destination, amount) in payments {
+        if amount == 0 {
+            continue;
+        }
+        match system_program_account {
+            Some(system_program_account) => {
+                transfer_lamports(source, destination, system_program_account, amount, signer_seeds)?
+            }
+            None => transfer_tokens(token_program, source, destination, authority, amount, signer_seeds)?,
+        }
+    }
+
+    provenance.sale_count += 1;
+    provenance.serialize(&mut *sale.provenance_account.data.borrow_mut())?;
+
+    Ok(breakdown)
+}
@@ -0,0 +1,271 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{config_program, load_config};
+use crate::events::{emit, VortexEvent};
+use crate::utils::{close_program_account, create_pda_account};
+use crate::VortexError;
+
+/// Seed of a membership record, followed by the role index and the member.
+/// Memberships are derived under `config_program` so every program sees them.
+pub const ROLE_SEED: &[u8] = b"role";
+
+/// Roles mirroring the access control of the Solidity contracts
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// Runs the genesis distribution and mints new art
+    Minter,
+    /// Executes passed proposals
+    Dao,
+    Treasurer,
+    /// Creates stake pools and sets their multiplier curves
+    RewardManager,
+}
+
+/// Record proving that `member` holds `role`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RoleMembership {
+    pub role: Role,
+    pub member: Pubkey,
+    pub granted_by: Pubkey,
+    pub bump: u8,
+}
+
+impl RoleMembership {
+    pub const LEN: usize = 1 + 32 + 32 + 1;
+}
+
+/// Address of the membership record of `member` for `role`
+pub fn find_membership_address(role: Role, member: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROLE_SEED, &[role as u8], member.as_ref()], &config_program::id())
+}
+
+/// Fail unless `member` signed and `membership` proves it holds `role`.
+pub fn require_role(
+    member: &AccountInfo,
+    membership: &AccountInfo,
+    role: Role,
+) -> ProgramResult {
+    // Verify member is signer
+    if !member.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (membership_key, _) = find_membership_address(role, member.key);
+    if *membership.key != membership_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *membership.owner != config_program::id() || membership.data_is_empty() {
+        msg!("Missing role {:?}", role);
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let record = RoleMembership::try_from_slice(&membership.data.borrow())?;
+    if record.role != role || record.member != *member.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    Ok(())
+}
+
+pub(crate) fn process_grant_role(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    role: Role,
+    member: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_config)?;
+    if config.admin != *admin.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    let (membership_key, membership_bump) = find_membership_address(role, &member);
+    if *membership_account.key != membership_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !membership_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    create_pda_account(
+        admin,
+        membership_account,
+        system_program,
+        program_id,
+        &rent,
+        RoleMembership::LEN,
+        &[ROLE_SEED, &[role as u8], member.as_ref(), &[membership_bump]],
+    )?;
+
+    let record = RoleMembership {
+        role,
+        member,
+        granted_by: *admin.key,
+        bump: membership_bump,
+    };
+    record.serialize(&mut *membership_account.data.borrow_mut())?;
+
+    emit(VortexEvent::RoleGranted {
+        role,
+        member,
+        admin: *admin.key,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn process_revoke_role(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    role: Role,
+    member: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+
+    // Verify admin is signer
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_config)?;
+    if config.admin != *admin.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    close_membership(program_id, membership_account, admin, role, &member)?;
+
+    emit(VortexEvent::RoleRevoked {
+        role,
+        member,
+        revoked_by: *admin.key,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn process_renounce_role(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    role: Role,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let member = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+
+    // Verify member is signer
+    if !member.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    close_membership(program_id, membership_account, member, role, member.key)?;
+
+    emit(VortexEvent::RoleRevoked {
+        role,
+        member: *member.key,
+        revoked_by: *member.key,
+    });
+
+    Ok(())
+}
+
+// Delete a membership record, sending its lamports to `receiver`
+fn close_membership(
+    program_id: &Pubkey,
+    membership_account: &AccountInfo,
+    receiver: &AccountInfo,
+    role: Role,
+    member: &Pubkey,
+) -> ProgramResult {
+    let (membership_key, _) = find_membership_address(role, member);
+    if *membership_account.key != membership_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if membership_account.owner != program_id || membership_account.data_is_empty() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    close_program_account(membership_account, receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{self, ConfigInstruction};
+    use crate::test_utils::{self, empty, grant_role, initialized_config, run, wallet};
+
+    #[test]
+    fn test_membership_address_is_per_role_and_member() {
+        let member = Pubkey::new_unique();
+
+        let (minter, _) = find_membership_address(Role::Minter, &member);
+        let (dao, _) = find_membership_address(Role::Dao, &member);
+        let (other, _) = find_membership_address(Role::Minter, &Pubkey::new_unique());
+
+        assert_ne!(minter, dao);
+        assert_ne!(minter, other);
+    }
+
+    #[test]
+    fn test_membership_len() {
+        let record = RoleMembership {
+            role: Role::RewardManager,
+            member: Pubkey::new_unique(),
+            granted_by: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(record.try_to_vec().unwrap().len(), RoleMembership::LEN);
+    }
+
+    #[test]
+    fn test_granted_role_is_required_through_dispatcher() {
+        test_utils::install();
+        let admin = wallet();
+        let program_config = initialized_config(&admin, &Pubkey::new_unique());
+        let minter = wallet();
+        let membership = empty(find_membership_address(Role::Minter, minter.key).0);
+
+        // Only the config admin can grant
+        let impostor = wallet();
+        assert_eq!(
+            grant_role(&impostor, &program_config, &membership, Role::Minter, minter.key).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+
+        grant_role(&admin, &program_config, &membership, Role::Minter, minter.key).unwrap();
+        assert!(require_role(&minter, &membership, Role::Minter).is_ok());
+        assert_eq!(
+            require_role(&minter, &membership, Role::Dao).unwrap_err(),
+            ProgramError::InvalidSeeds
+        );
+
+        let data = ConfigInstruction::RenounceRole { role: Role::Minter }.try_to_vec().unwrap();
+        run(config::process_instruction, &config_program::id(), &[minter.clone(), membership.clone()], &data).unwrap();
+        assert_eq!(
+            require_role(&minter, &membership, Role::Minter).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+    }
+}
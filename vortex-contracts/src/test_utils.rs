@@ -0,0 +1,345 @@
+//! In-process stand-in for the runtime, so unit tests can drive the
+//! instruction dispatchers end to end. Cross-program invocations of the
+//! system and token programs are emulated on the passed accounts.
+
+use std::sync::{Mutex, Once};
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    system_instruction::SystemInstruction,
+    system_program,
+    sysvar,
+};
+use borsh::BorshSerialize;
+use spl_token::{
+    instruction::{AuthorityType, TokenInstruction},
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+use crate::config::{self, config_program, find_config_address, ConfigInstruction};
+use crate::marketplace::metadata::{find_metadata_address, metadata_program, METADATA_V1_KEY};
+use crate::roles::Role;
+
+/// Unix timestamp every test instruction runs at
+pub const NOW: i64 = 1_700_000_000;
+
+// Every account data buffer handed out, as (address, length)
+static BUFFERS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+
+fn leak_buffer(data: Vec<u8>) -> &'static mut [u8] {
+    let buffer = Box::leak(data.into_boxed_slice());
+    BUFFERS.lock().unwrap().push((buffer.as_ptr() as usize, buffer.len()));
+    buffer
+}
+
+/// Build an account; everything is leaked, so the account lives for the whole test
+pub fn account(key: Pubkey, owner: Pubkey, lamports: u64, data: Vec<u8>, is_signer: bool) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        is_signer,
+        true,
+        Box::leak(Box::new(lamports)),
+        leak_buffer(data),
+        Box::leak(Box::new(owner)),
+        false,
+        0,
+    )
+}
+
+/// Run one instruction, then give every account its full data back.
+///
+/// Serializing through `&mut *data.borrow_mut()` advances the account's data
+/// slice; the runtime starts each instruction from the stored account, so
+/// this does the same.
+pub fn run(
+    processor: fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult,
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'static>],
+    data: &[u8],
+) -> ProgramResult {
+    let result = processor(program_id, accounts, data);
+    let buffers = BUFFERS.lock().unwrap();
+    for account in accounts {
+        let mut data = account.data.borrow_mut();
+        let end = data.as_ptr() as usize + data.len();
+        if let Some(&(start, len)) = buffers.iter().find(|(start, len)| start + len == end && *len > 0) {
+            *data = unsafe { std::slice::from_raw_parts_mut(start as *mut u8, len) };
+        }
+    }
+    result
+}
+
+/// A funded signing wallet
+pub fn wallet() -> AccountInfo<'static> {
+    account(Pubkey::new_unique(), system_program::id(), 10_000_000_000, Vec::new(), true)
+}
+
+/// An empty account at `key`, as a PDA looks before it's created
+pub fn empty(key: Pubkey) -> AccountInfo<'static> {
+    account(key, system_program::id(), 0, Vec::new(), false)
+}
+
+/// An executable program account
+pub fn program(key: Pubkey) -> AccountInfo<'static> {
+    let info = account(key, Pubkey::default(), 1, Vec::new(), false);
+    AccountInfo { executable: true, ..info }
+}
+
+/// The rent sysvar with the default rent
+pub fn rent_sysvar() -> AccountInfo<'static> {
+    let mut data = 3_480u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&2.0f64.to_le_bytes());
+    data.push(50);
+    account(sysvar::rent::id(), sysvar::id(), 1, data, false)
+}
+
+/// Program data of `program_id`, upgradeable by `upgrade_authority`
+pub fn program_data(program_id: &Pubkey, upgrade_authority: &Pubkey) -> AccountInfo<'static> {
+    let (key, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(upgrade_authority.as_ref());
+    account(key, bpf_loader_upgradeable::id(), 1, data, false)
+}
+
+/// An initialized 0-decimal mint
+pub fn mint(authority: &Pubkey) -> AccountInfo<'static> {
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(
+        Mint {
+            mint_authority: COption::Some(*authority),
+            supply: 0,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    account(Pubkey::new_unique(), spl_token::id(), 1, data, false)
+}
+
+/// A token account of `owner` holding `amount` of `mint`
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> AccountInfo<'static> {
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount::pack(
+        TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: AccountState::Initialized,
+            ..TokenAccount::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    account(Pubkey::new_unique(), spl_token::id(), 1, data, false)
+}
+
+/// Token balance of a token account
+pub fn balance(token_account: &AccountInfo) -> u64 {
+    TokenAccount::unpack(&token_account.data.borrow()).unwrap().amount
+}
+
+/// Serialize a metadata account the way the metadata program lays it out
+pub fn metadata_with_creators(
+    mint: &Pubkey,
+    creators: &[(Pubkey, bool, u8)],
+    collection: Option<(bool, Pubkey)>,
+) -> Vec<u8> {
+    let mut data = vec![METADATA_V1_KEY];
+    Pubkey::new_unique().serialize(&mut data).unwrap();
+    mint.serialize(&mut data).unwrap();
+    // Names are padded with nul bytes inside the string
+    "Vortex #1\0\0\0".to_string().serialize(&mut data).unwrap();
+    "VRTX".to_string().serialize(&mut data).unwrap();
+    "https://vortexartec.com/1.json".to_string().serialize(&mut data).unwrap();
+    500u16.serialize(&mut data).unwrap();
+    Some(creators.to_vec()).serialize(&mut data).unwrap();
+    false.serialize(&mut data).unwrap();
+    true.serialize(&mut data).unwrap();
+    Some(254u8).serialize(&mut data).unwrap();
+    Some(0u8).serialize(&mut data).unwrap();
+    collection.serialize(&mut data).unwrap();
+    // Uses, collection details and trailing padding
+    data.extend_from_slice(&[0; 64]);
+    data
+}
+
+/// Metadata account of `mint` naming `artist` as its verified creator
+pub fn metadata(mint: &Pubkey, artist: &Pubkey) -> AccountInfo<'static> {
+    let data = metadata_with_creators(mint, &[(*artist, true, 100)], None);
+    account(find_metadata_address(mint).0, metadata_program::id(), 1, data, false)
+}
+
+/// The shared config, initialized through the config program with `admin` as admin
+pub fn initialized_config(admin: &AccountInfo<'static>, guardian: &Pubkey) -> AccountInfo<'static> {
+    let config_account = empty(find_config_address().0);
+    let accounts = [
+        admin.clone(),
+        config_account.clone(),
+        program_data(&config_program::id(), admin.key),
+        program(system_program::id()),
+        rent_sysvar(),
+    ];
+    let data = ConfigInstruction::InitializeConfig { guardian: *guardian }.try_to_vec().unwrap();
+    run(config::process_instruction, &config_program::id(), &accounts, &data).unwrap();
+    config_account
+}
+
+/// Grant `role` to `member` through the config program, signed by `admin`
+pub fn grant_role(
+    admin: &AccountInfo<'static>,
+    program_config: &AccountInfo<'static>,
+    membership: &AccountInfo<'static>,
+    role: Role,
+    member: &Pubkey,
+) -> ProgramResult {
+    let accounts = [
+        admin.clone(),
+        program_config.clone(),
+        membership.clone(),
+        program(system_program::id()),
+        rent_sysvar(),
+    ];
+    let data = ConfigInstruction::GrantRole { role, member: *member }.try_to_vec().unwrap();
+    run(config::process_instruction, &config_program::id(), &accounts, &data)
+}
+
+/// Route syscalls of every test through the emulated runtime
+pub fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(TestRuntime));
+    });
+}
+
+struct TestRuntime;
+
+impl SyscallStubs for TestRuntime {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let find = |index: usize| -> Result<&AccountInfo, ProgramError> {
+            let key = instruction.accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys)?.pubkey;
+            account_infos.iter().find(|info| *info.key == key).ok_or(ProgramError::NotEnoughAccountKeys)
+        };
+
+        if instruction.program_id == system_program::id() {
+            let system_instruction: SystemInstruction = limited_deserialize(&instruction.data, 1_232)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            match system_instruction {
+                SystemInstruction::CreateAccount { lamports, space, owner } => {
+                    let (from, to) = (find(0)?, find(1)?);
+                    if !to.data_is_empty() {
+                        return Err(ProgramError::AccountAlreadyInitialized);
+                    }
+                    move_lamports(from, to, lamports)?;
+                    *to.data.borrow_mut() = leak_buffer(vec![0; space as usize]);
+                    to.assign(&owner);
+                    Ok(())
+                }
+                SystemInstruction::Transfer { lamports } => move_lamports(find(0)?, find(1)?, lamports),
+                _ => Err(ProgramError::InvalidInstructionData),
+            }
+        } else if instruction.program_id == spl_token::id() {
+            match TokenInstruction::unpack(&instruction.data)? {
+                TokenInstruction::InitializeMint2 { decimals, mint_authority, freeze_authority } => {
+                    let state = Mint {
+                        mint_authority: COption::Some(mint_authority),
+                        decimals,
+                        is_initialized: true,
+                        freeze_authority,
+                        ..Mint::default()
+                    };
+                    Mint::pack(state, &mut find(0)?.data.borrow_mut())
+                }
+                TokenInstruction::MintTo { amount } => {
+                    let (mint, destination, authority) = (find(0)?, find(1)?, find(2)?);
+                    let mut supply = Mint::unpack(&mint.data.borrow())?;
+                    let mut to = TokenAccount::unpack(&destination.data.borrow())?;
+                    if supply.mint_authority != COption::Some(*authority.key) || to.mint != *mint.key {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                    supply.supply += amount;
+                    to.amount += amount;
+                    Mint::pack(supply, &mut mint.data.borrow_mut())?;
+                    TokenAccount::pack(to, &mut destination.data.borrow_mut())
+                }
+                TokenInstruction::SetAuthority { authority_type: AuthorityType::MintTokens, new_authority } => {
+                    let (mint, authority) = (find(0)?, find(1)?);
+                    let mut state = Mint::unpack(&mint.data.borrow())?;
+                    if state.mint_authority != COption::Some(*authority.key) {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                    state.mint_authority = new_authority;
+                    Mint::pack(state, &mut mint.data.borrow_mut())
+                }
+                TokenInstruction::InitializeAccount3 { owner } => {
+                    let (token_account, mint) = (find(0)?, find(1)?);
+                    let state = TokenAccount {
+                        mint: *mint.key,
+                        owner,
+                        state: AccountState::Initialized,
+                        ..TokenAccount::default()
+                    };
+                    TokenAccount::pack(state, &mut token_account.data.borrow_mut())
+                }
+                TokenInstruction::Transfer { amount } => {
+                    let (source, destination, authority) = (find(0)?, find(1)?, find(2)?);
+                    let mut from = TokenAccount::unpack(&source.data.borrow())?;
+                    let mut to = TokenAccount::unpack(&destination.data.borrow())?;
+                    if from.owner != *authority.key || from.mint != to.mint {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                    from.amount = from.amount.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+                    to.amount += amount;
+                    TokenAccount::pack(from, &mut source.data.borrow_mut())?;
+                    TokenAccount::pack(to, &mut destination.data.borrow_mut())
+                }
+                TokenInstruction::CloseAccount => {
+                    let (token_account, destination) = (find(0)?, find(1)?);
+                    if TokenAccount::unpack(&token_account.data.borrow())?.amount != 0 {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                    move_lamports(token_account, destination, token_account.lamports())?;
+                    token_account.data.borrow_mut().fill(0);
+                    Ok(())
+                }
+                _ => Err(ProgramError::InvalidInstructionData),
+            }
+        } else {
+            Err(ProgramError::IncorrectProgramId)
+        }
+    }
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    let remaining = from.lamports().checked_sub(lamports).ok_or(ProgramError::InsufficientFunds)?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
@@ -73,6 +73,8 @@ async fn test_token_marketplace_integration() {
     let list_artwork = MarketplaceInstruction::ListArtwork {
         price: 100_000,
         royalty_percentage: 5,
+        expires_at: None,
+        payment_mint: None,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -84,7 +86,8 @@ async fn test_token_marketplace_integration() {
 
     // Purchase artwork
     let purchase = MarketplaceInstruction::PurchaseArtwork {
-        price: 100_000,
+        max_price: 100_000,
+        expected_nonce: 0,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -236,6 +239,8 @@ async fn test_full_marketplace_flow() {
     let list_artwork = MarketplaceInstruction::ListArtwork {
         price: 100_000,
         royalty_percentage: 5,
+        expires_at: None,
+        payment_mint: None,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -247,7 +252,8 @@ async fn test_full_marketplace_flow() {
 
     // Collector purchases artwork
     let purchase = MarketplaceInstruction::PurchaseArtwork {
-        price: 100_000,
+        max_price: 100_000,
+        expected_nonce: 0,
     };
 
     let mut transaction = Transaction::new_with_payer(
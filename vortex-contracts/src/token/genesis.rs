@@ -0,0 +1,440 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::roles::{require_role, Role};
+use crate::token::vesting::{create_vesting_accounts, is_valid_schedule, VestingAccount};
+use crate::token::{TolaState, STATE_SEED};
+use crate::utils::{create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+/// Seed of a liquid bucket's token vault, followed by the bucket index
+pub const GENESIS_VAULT_SEED: &[u8] = b"genesis_vault";
+
+/// Basis points making up the whole supply
+pub const TOTAL_BPS: u64 = 10_000;
+
+/// Allocation buckets of the 50M TOLA table in TOLA-Supply.md
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum GenesisBucket {
+    Community,
+    RoyaltyPool,
+    DaoTreasury,
+    Team,
+    Partners,
+    CreatorReserve,
+}
+
+impl GenesisBucket {
+    /// Role allowed to spend the bucket's vault
+    pub fn withdraw_role(&self) -> Role {
+        match self {
+            GenesisBucket::DaoTreasury => Role::Dao,
+            _ => Role::Treasurer,
+        }
+    }
+}
+
+/// Vesting terms of a bucket that unlocks over time
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct GenesisVesting {
+    pub beneficiary: Pubkey,
+    pub cliff_duration: i64,
+    pub period_duration: i64,
+    pub period_count: u32,
+    pub revocable: bool,
+}
+
+/// One row of the allocation table
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct GenesisAllocation {
+    pub bucket: GenesisBucket,
+    pub percentage_bps: u16,
+    /// Liquid buckets go to a program vault, vesting buckets to a vesting escrow
+    pub vesting: Option<GenesisVesting>,
+}
+
+/// Split `total_supply` according to `allocations`.
+///
+/// Every bucket must appear at most once, the percentages must add up to
+/// 100% and each share must be exact, so the amounts sum to the minted supply
+/// without rounding.
+pub fn allocation_amounts(
+    total_supply: u64,
+    allocations: &[GenesisAllocation],
+) -> Result<Vec<u64>, ProgramError> {
+    let mut total_bps = 0u64;
+    for (i, allocation) in allocations.iter().enumerate() {
+        if allocations[..i].iter().any(|other| other.bucket == allocation.bucket) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        total_bps += allocation.percentage_bps as u64;
+    }
+    if total_bps != TOTAL_BPS {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut amounts = Vec::with_capacity(allocations.len());
+    for allocation in allocations {
+        let scaled = total_supply as u128 * allocation.percentage_bps as u128;
+        let amount = scaled / TOTAL_BPS as u128;
+        if amount * TOTAL_BPS as u128 != scaled {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        amounts.push(amount as u64);
+    }
+
+    if amounts.iter().map(|amount| *amount as u128).sum::<u128>() != total_supply as u128 {
+        return Err(VortexError::InvalidState.into());
+    }
+
+    Ok(amounts)
+}
+
+// Move the minted supply into per-bucket vaults and vesting escrows
+pub fn process_distribute_genesis(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    allocations: Vec<GenesisAllocation>,
+    start_timestamp: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let supply_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let minter_membership = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Only minters may distribute the supply
+    require_role(authority, minter_membership, Role::Minter)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if state_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut state = TolaState::try_from_slice(&state_account.data.borrow())?;
+    if !state.initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if state.mint != *mint_account.key || state.supply_account != *supply_account.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    // One-shot
+    if state.genesis_distributed {
+        return Err(VortexError::InvalidState.into());
+    }
+
+    let amounts = allocation_amounts(state.total_supply, &allocations)?;
+    let rent = Rent::from_account_info(rent_account)?;
+    let state_seeds: &[&[u8]] = &[STATE_SEED, &[state.bump]];
+    // Revoked genesis grants return to the DAO, never to the minter
+    let (dao_treasury_vault, _) =
+        Pubkey::find_program_address(&[GENESIS_VAULT_SEED, &[GenesisBucket::DaoTreasury as u8]], program_id);
+
+    for (allocation, amount) in allocations.iter().zip(amounts) {
+        match &allocation.vesting {
+            None => {
+                let vault_account = next_account_info(account_info_iter)?;
+                let bucket_seed = [allocation.bucket as u8];
+                let (vault_key, vault_bump) =
+                    Pubkey::find_program_address(&[GENESIS_VAULT_SEED, &bucket_seed], program_id);
+                if *vault_account.key != vault_key {
+                    return Err(ProgramError::InvalidSeeds);
+                }
+
+                create_pda_token_account(
+                    authority,
+                    vault_account,
+                    mint_account,
+                    state_account.key,
+                    system_program,
+                    token_program,
+                    &rent,
+                    &[GENESIS_VAULT_SEED, &bucket_seed, &[vault_bump]],
+                )?;
+
+                if amount > 0 {
+                    transfer_tokens(
+                        token_program,
+                        supply_account,
+                        vault_account,
+                        state_account,
+                        amount,
+                        &[state_seeds],
+                    )?;
+                }
+            }
+            Some(terms) => {
+                let vesting_account = next_account_info(account_info_iter)?;
+                let escrow_account = next_account_info(account_info_iter)?;
+
+                if !is_valid_schedule(amount, terms.cliff_duration, terms.period_duration, terms.period_count) {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+
+                let (escrow_key, vesting_bump) = create_vesting_accounts(
+                    program_id,
+                    authority,
+                    vesting_account,
+                    escrow_account,
+                    mint_account,
+                    token_program,
+                    system_program,
+                    &rent,
+                    &terms.beneficiary,
+                )?;
+
+                transfer_tokens(
+                    token_program,
+                    supply_account,
+                    escrow_account,
+                    state_account,
+                    amount,
+                    &[state_seeds],
+                )?;
+
+                let vesting = VestingAccount {
+                    beneficiary: terms.beneficiary,
+                    authority: *authority.key,
+                    mint: *mint_account.key,
+                    escrow: escrow_key,
+                    treasury: dao_treasury_vault,
+                    total_amount: amount,
+                    released_amount: 0,
+                    start_timestamp,
+                    cliff_duration: terms.cliff_duration,
+                    period_duration: terms.period_duration,
+                    period_count: terms.period_count,
+                    revocable: terms.revocable,
+                    revoked: false,
+                    bump: vesting_bump,
+                };
+                vesting.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+                emit(VortexEvent::VestingCreated {
+                    beneficiary: terms.beneficiary,
+                    amount,
+                    start_time: start_timestamp,
+                    duration: vesting.end_timestamp() - start_timestamp,
+                });
+            }
+        }
+    }
+
+    state.genesis_distributed = true;
+    state.serialize(&mut *state_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Pay out of a liquid bucket's vault, signed by the token state that owns it
+pub fn process_withdraw_genesis_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bucket: GenesisBucket,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let membership = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    require_role(authority, membership, bucket.withdraw_role())?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if state_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let state = TolaState::try_from_slice(&state_account.data.borrow())?;
+
+    let bucket_seed = [bucket as u8];
+    let (vault_key, _) = Pubkey::find_program_address(&[GENESIS_VAULT_SEED, &bucket_seed], program_id);
+    if *vault_account.key != vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    transfer_tokens(
+        token_program,
+        vault_account,
+        destination_account,
+        state_account,
+        amount,
+        &[&[STATE_SEED, &[state.bump]]],
+    )?;
+
+    emit(VortexEvent::GenesisVaultWithdrawn {
+        bucket,
+        authority: *authority.key,
+        destination: *destination_account.key,
+        amount,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roles::find_membership_address;
+    use crate::test_utils::{self, account, balance, empty, grant_role, initialized_config, program, run, token_account, wallet};
+    use crate::token::{process_instruction, BurnBreakdown, TolaInstruction};
+
+    const TOLA: u64 = 1_000_000_000;
+
+    fn liquid(bucket: GenesisBucket, percentage_bps: u16) -> GenesisAllocation {
+        GenesisAllocation { bucket, percentage_bps, vesting: None }
+    }
+
+    fn vesting(bucket: GenesisBucket, percentage_bps: u16) -> GenesisAllocation {
+        GenesisAllocation {
+            bucket,
+            percentage_bps,
+            vesting: Some(GenesisVesting {
+                beneficiary: Pubkey::new_unique(),
+                cliff_duration: 1,
+                period_duration: 1,
+                period_count: 1,
+                revocable: true,
+            }),
+        }
+    }
+
+    fn supply_table() -> Vec<GenesisAllocation> {
+        vec![
+            liquid(GenesisBucket::Community, 1_000),
+            liquid(GenesisBucket::RoyaltyPool, 1_500),
+            liquid(GenesisBucket::DaoTreasury, 500),
+            vesting(GenesisBucket::Team, 6_000),
+            vesting(GenesisBucket::Partners, 500),
+            vesting(GenesisBucket::CreatorReserve, 500),
+        ]
+    }
+
+    #[test]
+    fn test_supply_table_amounts() {
+        let amounts = allocation_amounts(50_000_000 * TOLA, &supply_table()).unwrap();
+        assert_eq!(
+            amounts,
+            vec![
+                5_000_000 * TOLA,
+                7_500_000 * TOLA,
+                2_500_000 * TOLA,
+                30_000_000 * TOLA,
+                2_500_000 * TOLA,
+                2_500_000 * TOLA,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_percentages_must_sum_to_supply() {
+        let mut table = supply_table();
+        table[0].percentage_bps = 900;
+        assert!(allocation_amounts(50_000_000 * TOLA, &table).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_bucket_rejected() {
+        let mut table = supply_table();
+        table[1].bucket = GenesisBucket::Community;
+        assert!(allocation_amounts(50_000_000 * TOLA, &table).is_err());
+    }
+
+    #[test]
+    fn test_inexact_share_rejected() {
+        assert!(allocation_amounts(50_000_001, &supply_table()).is_err());
+    }
+
+    #[test]
+    fn test_bucket_vault_is_spent_by_its_role() {
+        test_utils::install();
+        let program_id = Pubkey::new_unique();
+        let admin = wallet();
+        let program_config = initialized_config(&admin, &Pubkey::new_unique());
+
+        let (state_key, state_bump) = Pubkey::find_program_address(&[STATE_SEED], &program_id);
+        let state = TolaState {
+            total_supply: 1_000,
+            mint_authority: Pubkey::new_unique(),
+            initialized: true,
+            mint: Pubkey::new_unique(),
+            supply_account: Pubkey::new_unique(),
+            bump: state_bump,
+            total_burned: 0,
+            burned_by_reason: BurnBreakdown::default(),
+            genesis_distributed: true,
+            mint_authority_revoked: false,
+        };
+        let state_account = account(state_key, program_id, 1, state.try_to_vec().unwrap(), false);
+        let bucket_seed = [GenesisBucket::Community as u8];
+        let vault_key = Pubkey::find_program_address(&[GENESIS_VAULT_SEED, &bucket_seed], &program_id).0;
+        let vault = token_account(&state.mint, &state_key, 100);
+        let vault = AccountInfo { key: Box::leak(Box::new(vault_key)), ..vault };
+
+        let withdraw = |authority: &AccountInfo<'static>,
+                        membership: &AccountInfo<'static>,
+                        destination: &AccountInfo<'static>| {
+            let accounts = [
+                authority.clone(),
+                state_account.clone(),
+                vault.clone(),
+                destination.clone(),
+                program(spl_token::id()),
+                program_config.clone(),
+                membership.clone(),
+            ];
+            let data = TolaInstruction::WithdrawGenesisVault { bucket: GenesisBucket::Community, amount: 40 }
+                .try_to_vec()
+                .unwrap();
+            run(process_instruction, &program_id, &accounts, &data)
+        };
+
+        let treasurer = wallet();
+        let destination = token_account(&state.mint, treasurer.key, 0);
+        let membership = empty(find_membership_address(Role::Treasurer, treasurer.key).0);
+        assert_eq!(
+            withdraw(&treasurer, &membership, &destination).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+
+        grant_role(&admin, &program_config, &membership, Role::Treasurer, treasurer.key).unwrap();
+        withdraw(&treasurer, &membership, &destination).unwrap();
+        assert_eq!(balance(&vault), 60);
+        assert_eq!(balance(&destination), 40);
+    }
+
+    #[test]
+    fn test_dao_treasury_needs_dao_role() {
+        assert_eq!(GenesisBucket::DaoTreasury.withdraw_role(), Role::Dao);
+        assert_eq!(GenesisBucket::Community.withdraw_role(), Role::Treasurer);
+        assert_eq!(GenesisBucket::RoyaltyPool.withdraw_role(), Role::Treasurer);
+    }
+}
@@ -3,22 +3,57 @@ use solana_program::{
     entrypoint,
     entrypoint::ProgramResult,
     msg,
+    program::invoke,
     program_error::ProgramError,
+    program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, check_upgrade_authority, PausableModule};
 use spl_token::instruction::TokenInstruction;
+use crate::events::{emit, VortexEvent};
+use crate::roles::{require_role, Role};
+use crate::utils::{create_pda_account, create_pda_token_account};
+use crate::VortexError;
+
+pub mod genesis;
+pub mod staking;
+pub mod vesting;
+
+/// The TOLA token program, which owns the stake positions counted by governance
+pub mod tola_program {
+    solana_program::declare_id!("3KnZg99MSpZFC2Z7KpAW2sWyTvK1fuU1FshA4JVxbf6h");
+}
+
+/// Seed of the PDA holding the `TolaState`
+pub const STATE_SEED: &[u8] = b"tola_state";
+/// Seed of the program-owned token account receiving the initial supply
+pub const SUPPLY_SEED: &[u8] = b"tola_supply";
+/// TOLA uses 9 decimals, see TOLA-Supply.md
+pub const TOLA_DECIMALS: u8 = 9;
 
 // Token instruction enum
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum TolaInstruction {
     /// Initialize the TOLA token
     /// 
+    /// Creates the SPL mint, mints `total_supply` into the program-owned
+    /// supply account and records the `TolaState`. Can only run once, and
+    /// only by the upgrade authority of the token program.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` The account of the person deploying the contract
-    /// 1. `[writable]` The token mint account
+    /// 0. `[signer, writable]` The upgrade authority of the token program, becomes mint authority
+    /// 1. `[signer, writable]` The token mint account (uninitialized)
     /// 2. `[]` The rent sysvar
     /// 3. `[]` The token program
+    /// 4. `[writable]` The token state account (PDA of `["tola_state"]`)
+    /// 5. `[writable]` The supply token account (PDA of `["tola_supply", mint]`)
+    /// 6. `[]` The system program
+    /// 7. `[]` The program config account (PDA of `["config"]`)
+    /// 8. `[]` The program data account of the token program
     Initialize {
         /// Total supply of tokens
         total_supply: u64,
@@ -26,26 +61,260 @@ pub enum TolaInstruction {
 
     /// Create vesting schedule
     /// 
+    /// Escrows `amount` and unlocks it in `period_count` equal tranches, one
+    /// every `period_duration` seconds once `cliff_duration` has passed.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` The account creating the vesting schedule
-    /// 1. `[writable]` The vesting account
+    /// 0. `[signer, writable]` The account creating the vesting schedule, recorded as vesting authority
+    /// 1. `[writable]` The vesting account (PDA of `["vesting", creator, beneficiary]`)
     /// 2. `[writable]` The token account to vest
+    /// 3. `[]` The beneficiary
+    /// 4. `[writable]` The escrow token account (PDA of `["vesting_escrow", vesting account]`)
+    /// 5. `[]` The token mint
+    /// 6. `[]` The token program
+    /// 7. `[]` The system program
+    /// 8. `[]` The rent sysvar
+    /// 9. `[]` The program config account (PDA of `["config"]`)
+    /// 10. `[]` The treasury token account, receives the unvested remainder if the grant is revoked
     CreateVesting {
         amount: u64,
         start_timestamp: i64,
-        duration: i64,
+        cliff_duration: i64,
+        period_duration: i64,
+        period_count: u32,
+        /// Whether the vesting authority may revoke the grant
+        revocable: bool,
     },
 
+    /// Claim vested tokens
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The beneficiary
+    /// 1. `[writable]` The vesting account
+    /// 2. `[writable]` The escrow token account
+    /// 3. `[writable]` The beneficiary's token account
+    /// 4. `[]` The token program
+    /// 5. `[]` The program config account (PDA of `["config"]`)
+    ClaimVested {},
+
+    /// Revoke a revocable vesting schedule
+    /// 
+    /// Pays what has vested so far to the beneficiary and returns the
+    /// unvested remainder to the treasury recorded in the vesting account.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The vesting authority
+    /// 1. `[writable]` The vesting account
+    /// 2. `[writable]` The escrow token account
+    /// 3. `[writable]` The beneficiary's token account
+    /// 4. `[writable]` The treasury token account recorded in the vesting account
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    RevokeVesting {},
+
     /// Enable staking
     /// 
+    /// Locks `amount` in the pool's stake vault until `duration` seconds from
+    /// now, at least `MIN_LOCK_DURATION`. Staking again tops up the position
+    /// and never shortens its lock.
+    /// The position's multiplier is read from the staking config curve; an
+    /// uninitialized config stakes at 1x.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` The staker
-    /// 1. `[writable]` The stake account
+    /// 0. `[signer, writable]` The staker
+    /// 1. `[writable]` The stake account (PDA of `["stake", pool, staker]`)
     /// 2. `[writable]` The token account to stake from
+    /// 3. `[writable]` The stake pool account
+    /// 4. `[writable]` The pool's stake vault
+    /// 5. `[]` The token program
+    /// 6. `[]` The system program
+    /// 7. `[]` The rent sysvar
+    /// 8. `[]` The staking config account (PDA of `["staking_config", pool]`)
+    /// 9. `[]` The program config account (PDA of `["config"]`)
     Stake {
         amount: u64,
         duration: i64,
     },
+
+    /// Create the stake pool for a mint
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The account holding the reward manager role, recorded as pool authority
+    /// 1. `[writable]` The stake pool account (PDA of `["stake_pool", mint]`)
+    /// 2. `[]` The token mint
+    /// 3. `[writable]` The stake vault (PDA of `["stake_vault", pool]`)
+    /// 4. `[writable]` The reward vault (PDA of `["reward_vault", pool]`)
+    /// 5. `[]` The token program
+    /// 6. `[]` The system program
+    /// 7. `[]` The rent sysvar
+    /// 8. `[]` The program config account (PDA of `["config"]`)
+    /// 9. `[]` The reward manager's role membership (PDA of `["role", role, member]`)
+    InitializeStakePool {
+        /// Rewards streamed per second, shared between all stakers
+        reward_rate: u64,
+    },
+
+    /// Fund the reward vault
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The funder
+    /// 1. `[writable]` The stake pool account
+    /// 2. `[writable]` The funder's token account
+    /// 3. `[writable]` The reward vault
+    /// 4. `[]` The token program
+    /// 5. `[]` The program config account (PDA of `["config"]`)
+    FundStakeRewards {
+        amount: u64,
+    },
+
+    /// Withdraw staked tokens after the lock has expired
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The staker
+    /// 1. `[writable]` The stake account
+    /// 2. `[writable]` The staker's token account
+    /// 3. `[writable]` The stake pool account
+    /// 4. `[writable]` The pool's stake vault
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    Unstake {},
+
+    /// Claim accrued staking rewards
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The staker
+    /// 1. `[writable]` The stake account
+    /// 2. `[writable]` The staker's token account
+    /// 3. `[writable]` The stake pool account
+    /// 4. `[writable]` The pool's reward vault
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config account (PDA of `["config"]`)
+    ClaimStakeRewards {},
+
+    /// Re-weight a stake position as its lock bonus decays; anyone may call
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable]` The stake account
+    /// 1. `[writable]` The stake pool account
+    /// 2. `[]` The program config account (PDA of `["config"]`)
+    CheckpointStake {},
+
+    /// Set the lockup multiplier curve of a pool
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The account holding the reward manager role
+    /// 1. `[]` The stake pool account
+    /// 2. `[writable]` The staking config account (PDA of `["staking_config", pool]`)
+    /// 3. `[]` The system program
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The program config account (PDA of `["config"]`)
+    /// 6. `[]` The reward manager's role membership (PDA of `["role", role, member]`)
+    SetMultiplierCurve {
+        curve: Vec<staking::MultiplierPoint>,
+    },
+
+    /// Burn tokens and record why
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The owner of the token account
+    /// 1. `[writable]` The token account to burn from
+    /// 2. `[writable]` The token mint
+    /// 3. `[writable]` The token state account
+    /// 4. `[]` The token program
+    /// 5. `[]` The program config account (PDA of `["config"]`)
+    Burn {
+        amount: u64,
+        reason: BurnReason,
+    },
+
+    /// Distribute the initial supply across the allocation table, once
+    /// 
+    /// Liquid buckets are moved into a vault (PDA of `["genesis_vault", bucket]`),
+    /// vesting buckets into a new vesting account starting at `start_timestamp`
+    /// whose unvested remainder returns to the DAO treasury vault if revoked.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The account holding the minter role
+    /// 1. `[writable]` The token state account
+    /// 2. `[writable]` The supply token account
+    /// 3. `[]` The token mint
+    /// 4. `[]` The token program
+    /// 5. `[]` The system program
+    /// 6. `[]` The rent sysvar
+    /// 7. `[]` The program config account (PDA of `["config"]`)
+    /// 8. `[]` The minter's role membership (PDA of `["role", role, member]`)
+    /// 9. `[writable]` Remaining accounts, per allocation in order: the bucket vault of a liquid bucket, or the vesting account followed by its escrow token account
+    DistributeGenesis {
+        allocations: Vec<genesis::GenesisAllocation>,
+        start_timestamp: i64,
+    },
+
+    /// Hand the mint authority over to the DAO
+    /// 
+    /// The new authority must hold the DAO role and co-sign, so it can
+    /// later sign `RevokeMintAuthority` itself. A PDA that no program signs
+    /// for would lock the authority for good.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The current mint authority
+    /// 1. `[writable]` The token mint
+    /// 2. `[writable]` The token state account
+    /// 3. `[]` The token program
+    /// 4. `[]` The program config account (PDA of `["config"]`)
+    /// 5. `[signer]` The new mint authority
+    /// 6. `[]` The new authority's DAO role membership (PDA of `["role", role, member]`)
+    SetMintAuthority {
+        new_authority: Pubkey,
+    },
+
+    /// Remove the mint authority for good, fixing the supply
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The current mint authority
+    /// 1. `[writable]` The token mint
+    /// 2. `[writable]` The token state account
+    /// 3. `[]` The token program
+    /// 4. `[]` The program config account (PDA of `["config"]`)
+    RevokeMintAuthority {},
+
+    /// Spend from the vault of a liquid genesis bucket
+    /// 
+    /// The DAO treasury is spent by the DAO, every other bucket by a
+    /// treasurer, see `GenesisBucket::withdraw_role`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The account holding the bucket's role
+    /// 1. `[]` The token state account, which owns the vaults
+    /// 2. `[writable]` The bucket vault (PDA of `["genesis_vault", bucket]`)
+    /// 3. `[writable]` The destination token account
+    /// 4. `[]` The token program
+    /// 5. `[]` The program config account (PDA of `["config"]`)
+    /// 6. `[]` The signer's role membership (PDA of `["role", role, member]`)
+    WithdrawGenesisVault {
+        bucket: genesis::GenesisBucket,
+        amount: u64,
+    },
+}
+
+/// Why tokens were burned, see the deflation strategy in TOLA-Supply.md
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum BurnReason {
+    /// Share of platform fees
+    Fee,
+    /// Expired bonuses and unclaimed rewards
+    ExpiredRewards,
+    /// Premium event entry
+    Event,
+    Other,
+}
+
+// Burned amounts per reason
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct BurnBreakdown {
+    pub fees: u64,
+    pub expired_rewards: u64,
+    pub events: u64,
+    pub other: u64,
 }
 
 // Program state
@@ -54,6 +323,39 @@ pub struct TolaState {
     pub total_supply: u64,
     pub mint_authority: Pubkey,
     pub initialized: bool,
+    pub mint: Pubkey,
+    pub supply_account: Pubkey,
+    pub bump: u8,
+    pub total_burned: u64,
+    pub burned_by_reason: BurnBreakdown,
+    pub genesis_distributed: bool,
+    /// Set once the SPL mint authority is gone; `mint_authority` is then zeroed
+    pub mint_authority_revoked: bool,
+}
+
+impl TolaState {
+    pub const LEN: usize = 8 + 32 + 1 + 32 + 32 + 1 + 8 + 8 * 4 + 1 + 1;
+
+    /// Supply left after burns
+    pub fn current_supply(&self) -> u64 {
+        self.total_supply.saturating_sub(self.total_burned)
+    }
+
+    /// Add a burn to the running totals
+    pub fn record_burn(&mut self, amount: u64, reason: BurnReason) -> Result<(), ProgramError> {
+        let bucket = match reason {
+            BurnReason::Fee => &mut self.burned_by_reason.fees,
+            BurnReason::ExpiredRewards => &mut self.burned_by_reason.expired_rewards,
+            BurnReason::Event => &mut self.burned_by_reason.events,
+            BurnReason::Other => &mut self.burned_by_reason.other,
+        };
+        *bucket = bucket.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.total_burned = self
+            .total_burned
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
 }
 
 // Entry point
@@ -72,13 +374,81 @@ pub fn process_instruction(
             msg!("Instruction: Initialize");
             process_initialize(program_id, accounts, total_supply)
         }
-        TolaInstruction::CreateVesting { amount, start_timestamp, duration } => {
+        TolaInstruction::CreateVesting {
+            amount,
+            start_timestamp,
+            cliff_duration,
+            period_duration,
+            period_count,
+            revocable,
+        } => {
             msg!("Instruction: Create Vesting");
-            process_create_vesting(program_id, accounts, amount, start_timestamp, duration)
+            vesting::process_create_vesting(
+                program_id,
+                accounts,
+                amount,
+                start_timestamp,
+                cliff_duration,
+                period_duration,
+                period_count,
+                revocable,
+            )
+        }
+        TolaInstruction::ClaimVested {} => {
+            msg!("Instruction: Claim Vested");
+            vesting::process_claim_vested(program_id, accounts)
+        }
+        TolaInstruction::RevokeVesting {} => {
+            msg!("Instruction: Revoke Vesting");
+            vesting::process_revoke_vesting(program_id, accounts)
         }
         TolaInstruction::Stake { amount, duration } => {
             msg!("Instruction: Stake");
-            process_stake(program_id, accounts, amount, duration)
+            staking::process_stake(program_id, accounts, amount, duration)
+        }
+        TolaInstruction::InitializeStakePool { reward_rate } => {
+            msg!("Instruction: Initialize Stake Pool");
+            staking::process_initialize_stake_pool(program_id, accounts, reward_rate)
+        }
+        TolaInstruction::FundStakeRewards { amount } => {
+            msg!("Instruction: Fund Stake Rewards");
+            staking::process_fund_stake_rewards(program_id, accounts, amount)
+        }
+        TolaInstruction::Unstake {} => {
+            msg!("Instruction: Unstake");
+            staking::process_unstake(program_id, accounts)
+        }
+        TolaInstruction::ClaimStakeRewards {} => {
+            msg!("Instruction: Claim Stake Rewards");
+            staking::process_claim_stake_rewards(program_id, accounts)
+        }
+        TolaInstruction::CheckpointStake {} => {
+            msg!("Instruction: Checkpoint Stake");
+            staking::process_checkpoint_stake(program_id, accounts)
+        }
+        TolaInstruction::SetMultiplierCurve { curve } => {
+            msg!("Instruction: Set Multiplier Curve");
+            staking::process_set_multiplier_curve(program_id, accounts, curve)
+        }
+        TolaInstruction::Burn { amount, reason } => {
+            msg!("Instruction: Burn");
+            process_burn(program_id, accounts, amount, reason)
+        }
+        TolaInstruction::DistributeGenesis { allocations, start_timestamp } => {
+            msg!("Instruction: Distribute Genesis");
+            genesis::process_distribute_genesis(program_id, accounts, allocations, start_timestamp)
+        }
+        TolaInstruction::SetMintAuthority { new_authority } => {
+            msg!("Instruction: Set Mint Authority");
+            process_set_mint_authority(program_id, accounts, Some(new_authority))
+        }
+        TolaInstruction::RevokeMintAuthority {} => {
+            msg!("Instruction: Revoke Mint Authority");
+            process_set_mint_authority(program_id, accounts, None)
+        }
+        TolaInstruction::WithdrawGenesisVault { bucket, amount } => {
+            msg!("Instruction: Withdraw Genesis Vault");
+            genesis::process_withdraw_genesis_vault(program_id, accounts, bucket, amount)
         }
     }
 }
@@ -94,37 +464,449 @@ fn process_initialize(
     let mint_account = next_account_info(account_info_iter)?;
     let rent_account = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let supply_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let program_data = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
 
-    // Verify the initializer is the signer
-    if !initializer.is_signer {
+    // Verify the initializer and the new mint are signers
+    if !initializer.is_signer || !mint_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Initialize token mint
-    // Implementation details to be added
+    // Only whoever deployed the token program can claim the mint
+    check_upgrade_authority(program_id, program_data, initializer.key)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if total_supply == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (state_key, state_bump) = Pubkey::find_program_address(&[STATE_SEED], program_id);
+    if *state_account.key != state_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (supply_key, supply_bump) =
+        Pubkey::find_program_address(&[SUPPLY_SEED, mint_account.key.as_ref()], program_id);
+    if *supply_account.key != supply_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Refuse a second initialization
+    if state_account.owner == program_id && !state_account.data_is_empty() {
+        let state = TolaState::try_from_slice(&state_account.data.borrow())?;
+        if state.initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+
+    // Create and initialize the token mint
+    invoke(
+        &system_instruction::create_account(
+            initializer.key,
+            mint_account.key,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[initializer.clone(), mint_account.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            mint_account.key,
+            initializer.key,
+            None,
+            TOLA_DECIMALS,
+        )?,
+        &[mint_account.clone(), token_program.clone()],
+    )?;
+
+    // Create the supply account, owned by the state PDA
+    create_pda_token_account(
+        initializer,
+        supply_account,
+        mint_account,
+        &state_key,
+        system_program,
+        token_program,
+        &rent,
+        &[SUPPLY_SEED, mint_account.key.as_ref(), &[supply_bump]],
+    )?;
+
+    // Mint the total supply
+    invoke(
+        &spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint_account.key,
+            supply_account.key,
+            initializer.key,
+            &[],
+            total_supply,
+        )?,
+        &[
+            mint_account.clone(),
+            supply_account.clone(),
+            initializer.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    // Persist the token state
+    create_pda_account(
+        initializer,
+        state_account,
+        system_program,
+        program_id,
+        &rent,
+        TolaState::LEN,
+        &[STATE_SEED, &[state_bump]],
+    )?;
+
+    let state = TolaState {
+        total_supply,
+        mint_authority: *initializer.key,
+        initialized: true,
+        mint: *mint_account.key,
+        supply_account: supply_key,
+        bump: state_bump,
+        total_burned: 0,
+        burned_by_reason: BurnBreakdown::default(),
+        genesis_distributed: false,
+        mint_authority_revoked: false,
+    };
+    state.serialize(&mut *state_account.data.borrow_mut())?;
+
+    emit(VortexEvent::TokenInitialized {
+        mint: *mint_account.key,
+        total_supply,
+    });
 
     Ok(())
 }
 
-// Create vesting schedule
-fn process_create_vesting(
+// Burn tokens and keep the supply accounting in `TolaState`
+fn process_burn(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
-    start_timestamp: i64,
-    duration: i64,
+    reason: BurnReason,
 ) -> ProgramResult {
-    // Implementation details to be added
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify owner is signer
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if state_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut state = TolaState::try_from_slice(&state_account.data.borrow())?;
+    if !state.initialized || state.mint != *mint_account.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    invoke(
+        &spl_token::instruction::burn(
+            &spl_token::id(),
+            token_account.key,
+            mint_account.key,
+            owner.key,
+            &[],
+            amount,
+        )?,
+        &[
+            token_account.clone(),
+            mint_account.clone(),
+            owner.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    state.record_burn(amount, reason)?;
+    state.serialize(&mut *state_account.data.borrow_mut())?;
+
+    emit(VortexEvent::TokensBurned {
+        burner: *owner.key,
+        amount,
+        reason,
+        total_burned: state.total_burned,
+    });
+
     Ok(())
 }
 
-// Process staking
-fn process_stake(
+// Move or revoke the SPL mint authority, keeping `TolaState` in sync
+fn process_set_mint_authority(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    amount: u64,
-    duration: i64,
+    new_authority: Option<Pubkey>,
 ) -> ProgramResult {
-    // Implementation details to be added
+    let account_info_iter = &mut accounts.iter();
+    let current_authority = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify the current authority is signer
+    if !current_authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if state_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut state = TolaState::try_from_slice(&state_account.data.borrow())?;
+    if !state.initialized || state.mint != *mint_account.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if state.mint_authority_revoked {
+        return Err(VortexError::InvalidState.into());
+    }
+    if state.mint_authority != *current_authority.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+
+    // Only hand the authority to the DAO, which can sign for it later
+    if let Some(authority) = new_authority {
+        let new_authority_account = next_account_info(account_info_iter)?;
+        let dao_membership = next_account_info(account_info_iter)?;
+        if *new_authority_account.key != authority {
+            return Err(VortexError::InvalidAccount.into());
+        }
+        require_role(new_authority_account, dao_membership, Role::Dao)?;
+    }
+
+    invoke(
+        &spl_token::instruction::set_authority(
+            &spl_token::id(),
+            mint_account.key,
+            new_authority.as_ref(),
+            spl_token::instruction::AuthorityType::MintTokens,
+            current_authority.key,
+            &[],
+        )?,
+        &[
+            mint_account.clone(),
+            current_authority.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    match new_authority {
+        Some(authority) => state.mint_authority = authority,
+        None => {
+            state.mint_authority = Pubkey::default();
+            state.mint_authority_revoked = true;
+        }
+    }
+    state.serialize(&mut *state_account.data.borrow_mut())?;
+
+    emit(VortexEvent::MintAuthorityChanged {
+        previous_authority: *current_authority.key,
+        new_authority,
+    });
+
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{program_option::COption, system_program};
+    use crate::roles::find_membership_address;
+    use crate::test_utils::{
+        self, account, balance, empty, grant_role, initialized_config, program, program_data, rent_sysvar, run,
+        wallet,
+    };
+
+    fn state(total_supply: u64) -> TolaState {
+        TolaState {
+            total_supply,
+            mint_authority: Pubkey::new_unique(),
+            initialized: true,
+            mint: Pubkey::new_unique(),
+            supply_account: Pubkey::new_unique(),
+            bump: 255,
+            total_burned: 0,
+            burned_by_reason: BurnBreakdown::default(),
+            genesis_distributed: false,
+            mint_authority_revoked: false,
+        }
+    }
+
+    #[test]
+    fn test_record_burn_by_reason() {
+        let mut state = state(50_000_000);
+        state.record_burn(40_000, BurnReason::Fee).unwrap();
+        state.record_burn(50, BurnReason::Event).unwrap();
+        state.record_burn(9_950, BurnReason::ExpiredRewards).unwrap();
+        state.record_burn(50, BurnReason::Event).unwrap();
+
+        assert_eq!(state.total_burned, 50_050);
+        assert_eq!(state.burned_by_reason.fees, 40_000);
+        assert_eq!(state.burned_by_reason.expired_rewards, 9_950);
+        assert_eq!(state.burned_by_reason.events, 100);
+        assert_eq!(state.burned_by_reason.other, 0);
+        assert_eq!(state.current_supply(), 49_949_950);
+    }
+
+    #[test]
+    fn test_record_burn_overflow() {
+        let mut state = state(u64::MAX);
+        state.record_burn(u64::MAX, BurnReason::Other).unwrap();
+        assert!(state.record_burn(1, BurnReason::Fee).is_err());
+    }
+
+    #[test]
+    fn test_initialize_by_upgrade_authority_once() {
+        test_utils::install();
+        let program_id = Pubkey::new_unique();
+        let deployer = wallet();
+        let program_config = initialized_config(&deployer, &Pubkey::new_unique());
+        let state_key = Pubkey::find_program_address(&[STATE_SEED], &program_id).0;
+        let state_account = empty(state_key);
+
+        let initialize = |initializer: &AccountInfo<'static>| {
+            let mint_account = account(Pubkey::new_unique(), system_program::id(), 0, Vec::new(), true);
+            let supply_key = Pubkey::find_program_address(&[SUPPLY_SEED, mint_account.key.as_ref()], &program_id).0;
+            let supply_account = empty(supply_key);
+            let accounts = [
+                initializer.clone(),
+                mint_account.clone(),
+                rent_sysvar(),
+                program(spl_token::id()),
+                state_account.clone(),
+                supply_account.clone(),
+                program(system_program::id()),
+                program_config.clone(),
+                program_data(&program_id, deployer.key),
+            ];
+            let data = TolaInstruction::Initialize { total_supply: 50_000_000 }.try_to_vec().unwrap();
+            run(process_instruction, &program_id, &accounts, &data).map(|()| (mint_account, supply_account))
+        };
+
+        // Whoever lands first can't claim the token
+        assert_eq!(initialize(&wallet()).unwrap_err(), VortexError::Unauthorized.into());
+
+        let (mint_account, supply_account) = initialize(&deployer).unwrap();
+        let mint = spl_token::state::Mint::unpack(&mint_account.data.borrow()).unwrap();
+        assert_eq!(mint.decimals, TOLA_DECIMALS);
+        assert_eq!(mint.supply, 50_000_000);
+        assert_eq!(balance(&supply_account), 50_000_000);
+        let supply = spl_token::state::Account::unpack(&supply_account.data.borrow()).unwrap();
+        assert_eq!(supply.owner, state_key);
+
+        let state = TolaState::try_from_slice(&state_account.data.borrow()).unwrap();
+        assert!(state.initialized);
+        assert_eq!(state.mint, *mint_account.key);
+        assert_eq!(state.mint_authority, *deployer.key);
+
+        assert_eq!(initialize(&deployer).unwrap_err(), ProgramError::AccountAlreadyInitialized);
+    }
+
+    #[test]
+    fn test_mint_authority_goes_to_the_dao_then_revoked() {
+        test_utils::install();
+        let program_id = Pubkey::new_unique();
+        let admin = wallet();
+        let program_config = initialized_config(&admin, &Pubkey::new_unique());
+        let minter = wallet();
+        let mint_account = test_utils::mint(minter.key);
+        let (state_key, state_bump) = Pubkey::find_program_address(&[STATE_SEED], &program_id);
+        let state = TolaState {
+            mint_authority: *minter.key,
+            mint: *mint_account.key,
+            bump: state_bump,
+            ..state(1_000)
+        };
+        let state_account = account(state_key, program_id, 1, state.try_to_vec().unwrap(), false);
+
+        let set_authority = |authority: &AccountInfo<'static>, instruction: TolaInstruction, extra: &[AccountInfo<'static>]| {
+            let mut accounts = vec![
+                authority.clone(),
+                mint_account.clone(),
+                state_account.clone(),
+                program(spl_token::id()),
+                program_config.clone(),
+            ];
+            accounts.extend_from_slice(extra);
+            run(process_instruction, &program_id, &accounts, &instruction.try_to_vec().unwrap())
+        };
+        let to = |dao: &AccountInfo| TolaInstruction::SetMintAuthority { new_authority: *dao.key };
+        let load = || TolaState::try_from_slice(&state_account.data.borrow()).unwrap();
+
+        let dao = wallet();
+        let dao_membership = empty(find_membership_address(Role::Dao, dao.key).0);
+
+        // Only the current authority can hand it over
+        assert_eq!(
+            set_authority(&wallet(), to(&dao), &[dao.clone(), dao_membership.clone()]).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+        // And only to an account holding the DAO role
+        assert_eq!(
+            set_authority(&minter, to(&dao), &[dao.clone(), dao_membership.clone()]).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+
+        grant_role(&admin, &program_config, &dao_membership, Role::Dao, dao.key).unwrap();
+        set_authority(&minter, to(&dao), &[dao.clone(), dao_membership.clone()]).unwrap();
+        assert_eq!(load().mint_authority, *dao.key);
+        let mint = spl_token::state::Mint::unpack(&mint_account.data.borrow()).unwrap();
+        assert_eq!(mint.mint_authority, COption::Some(*dao.key));
+
+        // The DAO signs the revocation itself
+        assert_eq!(
+            set_authority(&minter, TolaInstruction::RevokeMintAuthority {}, &[]).unwrap_err(),
+            VortexError::Unauthorized.into()
+        );
+        set_authority(&dao, TolaInstruction::RevokeMintAuthority {}, &[]).unwrap();
+        let state = load();
+        assert!(state.mint_authority_revoked);
+        assert_eq!(state.mint_authority, Pubkey::default());
+        let mint = spl_token::state::Mint::unpack(&mint_account.data.borrow()).unwrap();
+        assert_eq!(mint.mint_authority, COption::None);
+
+        assert_eq!(
+            set_authority(&dao, TolaInstruction::RevokeMintAuthority {}, &[]).unwrap_err(),
+            VortexError::InvalidState.into()
+        );
+    }
+}
@@ -0,0 +1,873 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::roles::{require_role, Role};
+use crate::utils::{create_pda_account, create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+/// Seed of the stake pool PDA, followed by the staked mint
+pub const STAKE_POOL_SEED: &[u8] = b"stake_pool";
+/// Seed of the token account holding staked principal, followed by the pool
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+/// Seed of the token account holding funded rewards, followed by the pool
+pub const REWARD_VAULT_SEED: &[u8] = b"reward_vault";
+/// Seed of a staker's position PDA, followed by the pool and the staker
+pub const STAKE_POSITION_SEED: &[u8] = b"stake";
+/// Seed of the staking config PDA, followed by the pool
+pub const STAKING_CONFIG_SEED: &[u8] = b"staking_config";
+
+/// Fixed-point scale of `reward_per_token_stored`
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+/// Multiplier of an unlocked position, in basis points
+pub const BASE_MULTIPLIER_BPS: u32 = 10_000;
+/// Highest multiplier a curve may reach, in basis points
+pub const MAX_MULTIPLIER_BPS: u32 = 50_000;
+/// Shortest lock a stake may take, in seconds
+pub const MIN_LOCK_DURATION: i64 = 7 * 24 * 60 * 60;
+/// Maximum number of points on a multiplier curve
+pub const MAX_CURVE_POINTS: usize = 8;
+
+/// Pool-wide staking state.
+///
+/// Rewards stream out of the reward vault at `reward_rate` tokens per second,
+/// shared pro rata to each position's checkpointed `weight` through a
+/// reward-per-token accumulator. Streaming stops when the funded
+/// `reward_reserve` runs out.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct StakePool {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub stake_vault: Pubkey,
+    pub reward_vault: Pubkey,
+    pub reward_rate: u64,
+    pub reward_reserve: u64,
+    pub reward_per_token_stored: u128,
+    pub last_update_time: i64,
+    pub total_staked: u64,
+    pub total_weight: u64,
+    pub bump: u8,
+}
+
+impl StakePool {
+    pub const LEN: usize = 32 * 4 + 8 + 8 + 16 + 8 + 8 + 8 + 1;
+
+    /// Advance the accumulator to `now`
+    pub fn accrue(&mut self, now: i64) {
+        if now <= self.last_update_time {
+            return;
+        }
+
+        if self.total_weight > 0 {
+            let elapsed = (now - self.last_update_time) as u128;
+            let reward = (self.reward_rate as u128 * elapsed).min(self.reward_reserve as u128);
+            self.reward_per_token_stored += reward * REWARD_PRECISION / self.total_weight as u128;
+            self.reward_reserve -= reward as u64;
+        }
+
+        self.last_update_time = now;
+    }
+}
+
+/// A staker's locked position in a pool.
+///
+/// `lock_duration` is the lock length measured from the last stake and
+/// `multiplier_bps` the curve value for it, snapshotted at that time.
+/// `weight` is the effective weight as of the last checkpoint.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct StakePosition {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub lock_duration: i64,
+    pub lock_end: i64,
+    pub multiplier_bps: u32,
+    pub weight: u64,
+    pub reward_per_token_paid: u128,
+    pub pending_rewards: u64,
+    pub bump: u8,
+}
+
+impl StakePosition {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 4 + 8 + 16 + 8 + 1;
+
+    /// Move rewards earned since the last checkpoint into `pending_rewards`.
+    /// The pool must have been accrued first.
+    pub fn settle(&mut self, pool: &StakePool) {
+        let delta = pool.reward_per_token_stored - self.reward_per_token_paid;
+        self.pending_rewards += (self.weight as u128 * delta / REWARD_PRECISION) as u64;
+        self.reward_per_token_paid = pool.reward_per_token_stored;
+    }
+}
+
+/// One point of a lockup multiplier curve
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MultiplierPoint {
+    pub duration: i64,
+    pub multiplier_bps: u32,
+}
+
+/// Lockup-duration multiplier curve of a pool.
+///
+/// The curve starts at 1x for a zero lock, interpolates linearly between
+/// points and stays flat after the last one. Changing it only affects
+/// positions staked afterwards.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct StakingConfig {
+    pub pool: Pubkey,
+    pub curve: Vec<MultiplierPoint>,
+    pub bump: u8,
+}
+
+impl StakingConfig {
+    pub const LEN: usize = 32 + 4 + MAX_CURVE_POINTS * (8 + 4) + 1;
+
+    /// Durations must be positive and strictly increasing, multipliers at
+    /// least 1x, non-decreasing and at most `MAX_MULTIPLIER_BPS`
+    pub fn validate_curve(curve: &[MultiplierPoint]) -> bool {
+        let mut prev = MultiplierPoint { duration: 0, multiplier_bps: BASE_MULTIPLIER_BPS };
+        for point in curve {
+            if point.duration <= prev.duration
+                || point.multiplier_bps < prev.multiplier_bps
+                || point.multiplier_bps > MAX_MULTIPLIER_BPS
+            {
+                return false;
+            }
+            prev = *point;
+        }
+        curve.len() <= MAX_CURVE_POINTS
+    }
+
+    /// Multiplier for a lock of `lock_duration` seconds, in basis points
+    pub fn multiplier_bps(&self, lock_duration: i64) -> u32 {
+        let lock_duration = lock_duration.max(0);
+        let mut prev = MultiplierPoint { duration: 0, multiplier_bps: BASE_MULTIPLIER_BPS };
+        for point in &self.curve {
+            if lock_duration < point.duration {
+                let span = (point.duration - prev.duration) as u128;
+                let progress = (lock_duration - prev.duration) as u128;
+                let rise = (point.multiplier_bps - prev.multiplier_bps) as u128;
+                return prev.multiplier_bps + (rise * progress / span) as u32;
+            }
+            prev = *point;
+        }
+        prev.multiplier_bps
+    }
+}
+
+/// Voting and reward weight of a position at `now`.
+///
+/// The bonus above 1x decays linearly with the remaining lock, so the weight
+/// starts at `amount * multiplier` and reaches `amount` at unlock.
+pub fn effective_weight(position: &StakePosition, now: i64) -> u64 {
+    let bonus_bps = position.multiplier_bps.saturating_sub(BASE_MULTIPLIER_BPS) as u128;
+    let decayed_bonus_bps = if position.lock_duration > 0 {
+        let remaining = (position.lock_end - now).clamp(0, position.lock_duration) as u128;
+        bonus_bps * remaining / position.lock_duration as u128
+    } else {
+        0
+    };
+
+    (position.amount as u128 * (BASE_MULTIPLIER_BPS as u128 + decayed_bonus_bps)
+        / BASE_MULTIPLIER_BPS as u128) as u64
+}
+
+// Re-weight a settled position at `now`, keeping the pool total in sync
+fn checkpoint(pool: &mut StakePool, position: &mut StakePosition, now: i64) {
+    let weight = effective_weight(position, now);
+    pool.total_weight = pool.total_weight - position.weight + weight;
+    position.weight = weight;
+}
+
+fn load_pool(program_id: &Pubkey, pool_account: &AccountInfo) -> Result<StakePool, ProgramError> {
+    if pool_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(StakePool::try_from_slice(&pool_account.data.borrow())?)
+}
+
+fn load_position(
+    program_id: &Pubkey,
+    position_account: &AccountInfo,
+    staker: &Pubkey,
+    pool: &Pubkey,
+) -> Result<StakePosition, ProgramError> {
+    if position_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let position = StakePosition::try_from_slice(&position_account.data.borrow())?;
+    if position.owner != *staker {
+        return Err(VortexError::Unauthorized.into());
+    }
+    if position.pool != *pool {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    Ok(position)
+}
+
+fn load_config(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    pool: &Pubkey,
+) -> Result<Option<StakingConfig>, ProgramError> {
+    let (config_key, _) =
+        Pubkey::find_program_address(&[STAKING_CONFIG_SEED, pool.as_ref()], program_id);
+    if *config_account.key != config_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.data_is_empty() {
+        return Ok(None);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    // The curve is variable length, ignore the unused tail of the account
+    Ok(Some(StakingConfig::deserialize(&mut &config_account.data.borrow()[..])?))
+}
+
+// Create the stake pool and its vaults
+pub fn process_initialize_stake_pool(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reward_rate: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let stake_vault = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let manager_membership = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Only reward managers may create pools
+    require_role(authority, manager_membership, Role::RewardManager)?;
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (pool_key, pool_bump) =
+        Pubkey::find_program_address(&[STAKE_POOL_SEED, mint_account.key.as_ref()], program_id);
+    if *pool_account.key != pool_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !pool_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let (stake_vault_key, stake_vault_bump) =
+        Pubkey::find_program_address(&[STAKE_VAULT_SEED, pool_key.as_ref()], program_id);
+    let (reward_vault_key, reward_vault_bump) =
+        Pubkey::find_program_address(&[REWARD_VAULT_SEED, pool_key.as_ref()], program_id);
+    if *stake_vault.key != stake_vault_key || *reward_vault.key != reward_vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+
+    create_pda_account(
+        authority,
+        pool_account,
+        system_program,
+        program_id,
+        &rent,
+        StakePool::LEN,
+        &[STAKE_POOL_SEED, mint_account.key.as_ref(), &[pool_bump]],
+    )?;
+    create_pda_token_account(
+        authority,
+        stake_vault,
+        mint_account,
+        &pool_key,
+        system_program,
+        token_program,
+        &rent,
+        &[STAKE_VAULT_SEED, pool_key.as_ref(), &[stake_vault_bump]],
+    )?;
+    create_pda_token_account(
+        authority,
+        reward_vault,
+        mint_account,
+        &pool_key,
+        system_program,
+        token_program,
+        &rent,
+        &[REWARD_VAULT_SEED, pool_key.as_ref(), &[reward_vault_bump]],
+    )?;
+
+    let pool = StakePool {
+        authority: *authority.key,
+        mint: *mint_account.key,
+        stake_vault: stake_vault_key,
+        reward_vault: reward_vault_key,
+        reward_rate,
+        reward_reserve: 0,
+        reward_per_token_stored: 0,
+        last_update_time: Clock::get()?.unix_timestamp,
+        total_staked: 0,
+        total_weight: 0,
+        bump: pool_bump,
+    };
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Top up the reward vault
+pub fn process_fund_stake_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let funder = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let funder_token_account = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify funder is signer
+    if !funder.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut pool = load_pool(program_id, pool_account)?;
+    if pool.reward_vault != *reward_vault.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    pool.accrue(Clock::get()?.unix_timestamp);
+
+    transfer_tokens(token_program, funder_token_account, reward_vault, funder, amount, &[])?;
+
+    pool.reward_reserve = pool
+        .reward_reserve
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Lock `amount` for `duration` seconds, creating the position on first stake
+pub fn process_stake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    duration: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let position_account = next_account_info(account_info_iter)?;
+    let staker_token_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let stake_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify staker is signer
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if amount == 0 || duration < MIN_LOCK_DURATION {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut pool = load_pool(program_id, pool_account)?;
+    if pool.stake_vault != *stake_vault.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    let (position_key, position_bump) = Pubkey::find_program_address(
+        &[STAKE_POSITION_SEED, pool_account.key.as_ref(), staker.key.as_ref()],
+        program_id,
+    );
+    if *position_account.key != position_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    pool.accrue(now);
+
+    let mut position = if position_account.data_is_empty() {
+        let rent = Rent::from_account_info(rent_account)?;
+        create_pda_account(
+            staker,
+            position_account,
+            system_program,
+            program_id,
+            &rent,
+            StakePosition::LEN,
+            &[
+                STAKE_POSITION_SEED,
+                pool_account.key.as_ref(),
+                staker.key.as_ref(),
+                &[position_bump],
+            ],
+        )?;
+        StakePosition {
+            owner: *staker.key,
+            pool: *pool_account.key,
+            amount: 0,
+            lock_duration: 0,
+            lock_end: 0,
+            multiplier_bps: BASE_MULTIPLIER_BPS,
+            weight: 0,
+            reward_per_token_paid: pool.reward_per_token_stored,
+            pending_rewards: 0,
+            bump: position_bump,
+        }
+    } else {
+        load_position(program_id, position_account, staker.key, pool_account.key)?
+    };
+
+    position.settle(&pool);
+
+    transfer_tokens(token_program, staker_token_account, stake_vault, staker, amount, &[])?;
+
+    // Topping up never shortens an existing lock; the multiplier is re-based
+    // on the whole remaining lock
+    position.amount += amount;
+    position.lock_end = position.lock_end.max(now + duration);
+    position.lock_duration = position.lock_end - now;
+    position.multiplier_bps = match load_config(program_id, config_account, pool_account.key)? {
+        Some(config) => config.multiplier_bps(position.lock_duration),
+        None => BASE_MULTIPLIER_BPS,
+    };
+    pool.total_staked += amount;
+    checkpoint(&mut pool, &mut position, now);
+
+    position.serialize(&mut *position_account.data.borrow_mut())?;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    emit(VortexEvent::StakeCreated {
+        staker: *staker.key,
+        amount,
+        duration,
+    });
+
+    Ok(())
+}
+
+// Withdraw the principal once the lock has expired; rewards stay claimable
+pub fn process_unstake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let position_account = next_account_info(account_info_iter)?;
+    let staker_token_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let stake_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify staker is signer
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut pool = load_pool(program_id, pool_account)?;
+    if pool.stake_vault != *stake_vault.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    let mut position = load_position(program_id, position_account, staker.key, pool_account.key)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    if now < position.lock_end {
+        return Err(VortexError::InvalidState.into());
+    }
+    if position.amount == 0 {
+        return Err(VortexError::InsufficientFunds.into());
+    }
+
+    pool.accrue(now);
+    position.settle(&pool);
+
+    let amount = position.amount;
+    transfer_tokens(
+        token_program,
+        stake_vault,
+        staker_token_account,
+        pool_account,
+        amount,
+        &[&[STAKE_POOL_SEED, pool.mint.as_ref(), &[pool.bump]]],
+    )?;
+
+    position.amount = 0;
+    pool.total_staked -= amount;
+    checkpoint(&mut pool, &mut position, now);
+
+    position.serialize(&mut *position_account.data.borrow_mut())?;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    emit(VortexEvent::Unstaked {
+        staker: *staker.key,
+        amount,
+    });
+
+    Ok(())
+}
+
+// Pay out accrued rewards; allowed at any time
+pub fn process_claim_stake_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let staker = next_account_info(account_info_iter)?;
+    let position_account = next_account_info(account_info_iter)?;
+    let staker_token_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify staker is signer
+    if !staker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut pool = load_pool(program_id, pool_account)?;
+    if pool.reward_vault != *reward_vault.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    let mut position = load_position(program_id, position_account, staker.key, pool_account.key)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    pool.accrue(now);
+    position.settle(&pool);
+    checkpoint(&mut pool, &mut position, now);
+
+    let amount = position.pending_rewards;
+    if amount == 0 {
+        return Err(VortexError::InsufficientFunds.into());
+    }
+
+    transfer_tokens(
+        token_program,
+        reward_vault,
+        staker_token_account,
+        pool_account,
+        amount,
+        &[&[STAKE_POOL_SEED, pool.mint.as_ref(), &[pool.bump]]],
+    )?;
+
+    position.pending_rewards = 0;
+
+    position.serialize(&mut *position_account.data.borrow_mut())?;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    emit(VortexEvent::RewardsClaimed {
+        staker: *staker.key,
+        amount,
+    });
+
+    Ok(())
+}
+
+// Permissionless re-weighting of a position as its lock bonus decays
+pub fn process_checkpoint_stake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let position_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    let mut pool = load_pool(program_id, pool_account)?;
+    if position_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut position = StakePosition::try_from_slice(&position_account.data.borrow())?;
+    if position.pool != *pool_account.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    pool.accrue(now);
+    position.settle(&pool);
+    checkpoint(&mut pool, &mut position, now);
+
+    position.serialize(&mut *position_account.data.borrow_mut())?;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Create or replace the pool's multiplier curve
+pub fn process_set_multiplier_curve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    curve: Vec<MultiplierPoint>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let manager_membership = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Only reward managers may change the curve
+    require_role(authority, manager_membership, Role::RewardManager)?;
+
+    load_pool(program_id, pool_account)?;
+
+    if !StakingConfig::validate_curve(&curve) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let (config_key, config_bump) = Pubkey::find_program_address(
+        &[STAKING_CONFIG_SEED, pool_account.key.as_ref()],
+        program_id,
+    );
+    if *config_account.key != config_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if config_account.data_is_empty() {
+        let rent = Rent::from_account_info(rent_account)?;
+        create_pda_account(
+            authority,
+            config_account,
+            system_program,
+            program_id,
+            &rent,
+            StakingConfig::LEN,
+            &[STAKING_CONFIG_SEED, pool_account.key.as_ref(), &[config_bump]],
+        )?;
+    }
+
+    let config = StakingConfig {
+        pool: *pool_account.key,
+        curve,
+        bump: config_bump,
+    };
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(reward_rate: u64, reward_reserve: u64) -> StakePool {
+        StakePool {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            stake_vault: Pubkey::new_unique(),
+            reward_vault: Pubkey::new_unique(),
+            reward_rate,
+            reward_reserve,
+            reward_per_token_stored: 0,
+            last_update_time: 0,
+            total_staked: 0,
+            total_weight: 0,
+            bump: 255,
+        }
+    }
+
+    fn position(pool: &StakePool, amount: u64) -> StakePosition {
+        StakePosition {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            amount,
+            lock_duration: 0,
+            lock_end: 0,
+            multiplier_bps: BASE_MULTIPLIER_BPS,
+            weight: amount,
+            reward_per_token_paid: pool.reward_per_token_stored,
+            pending_rewards: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_no_accrual_without_stakers() {
+        let mut pool = pool(10, 1_000);
+        pool.accrue(50);
+        assert_eq!(pool.reward_per_token_stored, 0);
+        assert_eq!(pool.reward_reserve, 1_000);
+        assert_eq!(pool.last_update_time, 50);
+    }
+
+    #[test]
+    fn test_rewards_split_pro_rata() {
+        let mut pool = pool(10, 1_000);
+        let mut alice = position(&pool, 300);
+        let mut bob = position(&pool, 100);
+        pool.total_weight = 400;
+
+        pool.accrue(40);
+        alice.settle(&pool);
+        bob.settle(&pool);
+
+        assert_eq!(alice.pending_rewards, 300);
+        assert_eq!(bob.pending_rewards, 100);
+        assert_eq!(pool.reward_reserve, 600);
+    }
+
+    #[test]
+    fn test_late_staker_earns_from_entry() {
+        let mut pool = pool(10, 1_000);
+        let mut alice = position(&pool, 100);
+        pool.total_weight = 100;
+
+        pool.accrue(10);
+        let mut bob = position(&pool, 100);
+        pool.total_weight = 200;
+
+        pool.accrue(20);
+        alice.settle(&pool);
+        bob.settle(&pool);
+
+        assert_eq!(alice.pending_rewards, 150);
+        assert_eq!(bob.pending_rewards, 50);
+    }
+
+    #[test]
+    fn test_accrual_capped_by_reserve() {
+        let mut pool = pool(10, 50);
+        let mut alice = position(&pool, 100);
+        pool.total_weight = 100;
+
+        pool.accrue(1_000);
+        alice.settle(&pool);
+
+        assert_eq!(alice.pending_rewards, 50);
+        assert_eq!(pool.reward_reserve, 0);
+    }
+
+    fn config() -> StakingConfig {
+        // 1x at no lock, 2x at one year, 4x at four years
+        StakingConfig {
+            pool: Pubkey::new_unique(),
+            curve: vec![
+                MultiplierPoint { duration: 365, multiplier_bps: 20_000 },
+                MultiplierPoint { duration: 4 * 365, multiplier_bps: 40_000 },
+            ],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_multiplier_curve_interpolates() {
+        let config = config();
+        assert_eq!(config.multiplier_bps(-5), BASE_MULTIPLIER_BPS);
+        assert_eq!(config.multiplier_bps(0), BASE_MULTIPLIER_BPS);
+        assert_eq!(config.multiplier_bps(73), 12_000);
+        assert_eq!(config.multiplier_bps(365), 20_000);
+        assert_eq!(config.multiplier_bps(2 * 365), 26_666);
+        assert_eq!(config.multiplier_bps(10 * 365), 40_000);
+    }
+
+    #[test]
+    fn test_curve_validation() {
+        assert!(StakingConfig::validate_curve(&config().curve));
+        assert!(StakingConfig::validate_curve(&[]));
+        assert!(!StakingConfig::validate_curve(&[
+            MultiplierPoint { duration: 100, multiplier_bps: 20_000 },
+            MultiplierPoint { duration: 100, multiplier_bps: 30_000 },
+        ]));
+        assert!(!StakingConfig::validate_curve(&[
+            MultiplierPoint { duration: 100, multiplier_bps: 9_000 },
+        ]));
+        assert!(!StakingConfig::validate_curve(&[
+            MultiplierPoint { duration: 100, multiplier_bps: 30_000 },
+            MultiplierPoint { duration: 200, multiplier_bps: 20_000 },
+        ]));
+        assert!(!StakingConfig::validate_curve(&[
+            MultiplierPoint { duration: 100, multiplier_bps: MAX_MULTIPLIER_BPS + 1 },
+        ]));
+    }
+
+    #[test]
+    fn test_effective_weight_decays_to_amount() {
+        let pool = pool(0, 0);
+        let mut stake = position(&pool, 1_000);
+        stake.lock_duration = 365;
+        stake.lock_end = 1_365;
+        stake.multiplier_bps = 20_000;
+
+        assert_eq!(effective_weight(&stake, 1_000), 2_000);
+        assert_eq!(effective_weight(&stake, 1_000 + 365 / 2 + 1), 1_498);
+        assert_eq!(effective_weight(&stake, 1_365), 1_000);
+        assert_eq!(effective_weight(&stake, 5_000), 1_000);
+    }
+
+    #[test]
+    fn test_checkpoint_tracks_pool_weight() {
+        let mut pool = pool(0, 0);
+        let mut stake = position(&pool, 1_000);
+        stake.lock_duration = 100;
+        stake.lock_end = 100;
+        stake.multiplier_bps = 30_000;
+        stake.weight = 0;
+
+        checkpoint(&mut pool, &mut stake, 0);
+        assert_eq!(pool.total_weight, 3_000);
+
+        checkpoint(&mut pool, &mut stake, 50);
+        assert_eq!(stake.weight, 2_000);
+        assert_eq!(pool.total_weight, 2_000);
+    }
+}
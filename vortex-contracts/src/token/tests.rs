@@ -95,7 +95,10 @@ async fn test_vesting_schedule() {
     let vesting_instruction = TolaInstruction::CreateVesting {
         amount: 100_000,
         start_timestamp: 1_000_000,
-        duration: 365 * 24 * 60 * 60, // 1 year
+        cliff_duration: 180 * 24 * 60 * 60, // 6 months
+        period_duration: 30 * 24 * 60 * 60, // monthly
+        period_count: 12,
+        revocable: true,
     };
 
     // Create transaction
@@ -0,0 +1,532 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::config::{assert_not_paused, PausableModule};
+use crate::events::{emit, VortexEvent};
+use crate::utils::{create_pda_account, create_pda_token_account, transfer_tokens};
+use crate::VortexError;
+
+/// Seed of the vesting account PDA, followed by the grantor and the beneficiary
+pub const VESTING_SEED: &[u8] = b"vesting";
+/// Seed of the token account escrowing a vesting grant, followed by the vesting account
+pub const VESTING_ESCROW_SEED: &[u8] = b"vesting_escrow";
+
+/// A cliff + periodic-tranche vesting grant.
+///
+/// Nothing unlocks before `start_timestamp + cliff_duration`. After the cliff,
+/// one of `period_count` equal tranches unlocks at the end of every
+/// `period_duration`, the last tranche carrying any rounding remainder.
+/// A revocable grant can be cut short by its `authority`, which returns the
+/// unvested remainder to the `treasury` fixed at creation, see
+/// `process_revoke_vesting`.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct VestingAccount {
+    pub beneficiary: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub escrow: Pubkey,
+    /// Token account receiving the unvested remainder on revocation
+    pub treasury: Pubkey,
+    pub total_amount: u64,
+    pub released_amount: u64,
+    pub start_timestamp: i64,
+    pub cliff_duration: i64,
+    pub period_duration: i64,
+    pub period_count: u32,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+impl VestingAccount {
+    pub const LEN: usize = 32 * 5 + 8 + 8 + 8 + 8 + 8 + 4 + 1 + 1 + 1;
+
+    /// Total amount unlocked at `now`, including what was already released
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        let unlock_start = self.start_timestamp.saturating_add(self.cliff_duration);
+        if now < unlock_start || self.period_duration <= 0 || self.period_count == 0 {
+            return 0;
+        }
+
+        let elapsed_periods = ((now - unlock_start) / self.period_duration) as u64;
+        let unlocked_periods = elapsed_periods.min(self.period_count as u64);
+
+        (self.total_amount as u128 * unlocked_periods as u128 / self.period_count as u128) as u64
+    }
+
+    /// Amount that can be claimed at `now`
+    pub fn releasable_amount(&self, now: i64) -> u64 {
+        self.vested_amount(now).saturating_sub(self.released_amount)
+    }
+
+    /// Signer seeds of the vesting PDA, which owns the escrow
+    pub fn signer_seeds<'a>(&'a self, bump: &'a [u8]) -> [&'a [u8]; 4] {
+        [VESTING_SEED, self.authority.as_ref(), self.beneficiary.as_ref(), bump]
+    }
+
+    /// Timestamp at which the whole grant is unlocked
+    pub fn end_timestamp(&self) -> i64 {
+        self.start_timestamp
+            .saturating_add(self.cliff_duration)
+            .saturating_add(self.period_duration.saturating_mul(self.period_count as i64))
+    }
+}
+
+/// Address of `authority`'s grant to `beneficiary`. Keyed by grantor so that
+/// nobody else can take a beneficiary's grant address.
+pub fn find_vesting_address(program_id: &Pubkey, authority: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VESTING_SEED, authority.as_ref(), beneficiary.as_ref()], program_id)
+}
+
+pub(crate) fn is_valid_schedule(
+    amount: u64,
+    cliff_duration: i64,
+    period_duration: i64,
+    period_count: u32,
+) -> bool {
+    amount > 0 && cliff_duration >= 0 && period_duration > 0 && period_count > 0
+}
+
+// Create the vesting PDA of `authority`'s grant to `beneficiary` and its
+// escrow token account, paid by `authority`. Returns the escrow address and
+// the vesting account bump.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_vesting_accounts<'a>(
+    program_id: &Pubkey,
+    authority: &AccountInfo<'a>,
+    vesting_account: &AccountInfo<'a>,
+    escrow_account: &AccountInfo<'a>,
+    mint_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+    beneficiary: &Pubkey,
+) -> Result<(Pubkey, u8), ProgramError> {
+    let (vesting_key, vesting_bump) = find_vesting_address(program_id, authority.key, beneficiary);
+    if *vesting_account.key != vesting_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (escrow_key, escrow_bump) =
+        Pubkey::find_program_address(&[VESTING_ESCROW_SEED, vesting_key.as_ref()], program_id);
+    if *escrow_account.key != escrow_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !vesting_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    create_pda_account(
+        authority,
+        vesting_account,
+        system_program,
+        program_id,
+        rent,
+        VestingAccount::LEN,
+        &[VESTING_SEED, authority.key.as_ref(), beneficiary.as_ref(), &[vesting_bump]],
+    )?;
+
+    // Escrow token account, owned by the vesting PDA
+    create_pda_token_account(
+        authority,
+        escrow_account,
+        mint_account,
+        &vesting_key,
+        system_program,
+        token_program,
+        rent,
+        &[VESTING_ESCROW_SEED, vesting_key.as_ref(), &[escrow_bump]],
+    )?;
+
+    Ok((escrow_key, vesting_bump))
+}
+
+// Create a vesting schedule, escrowing `amount` from the creator
+#[allow(clippy::too_many_arguments)]
+pub fn process_create_vesting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    start_timestamp: i64,
+    cliff_duration: i64,
+    period_duration: i64,
+    period_count: u32,
+    revocable: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let creator = next_account_info(account_info_iter)?;
+    let vesting_account = next_account_info(account_info_iter)?;
+    let source_token_account = next_account_info(account_info_iter)?;
+    let beneficiary = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_account = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify creator is signer
+    if !creator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // A revocation can only ever return the remainder here
+    let treasury_token = spl_token::state::Account::unpack(&treasury_token_account.data.borrow())?;
+    if treasury_token.mint != *mint_account.key {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    if !is_valid_schedule(amount, cliff_duration, period_duration, period_count) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let rent = Rent::from_account_info(rent_account)?;
+    let (escrow_key, vesting_bump) = create_vesting_accounts(
+        program_id,
+        creator,
+        vesting_account,
+        escrow_account,
+        mint_account,
+        token_program,
+        system_program,
+        &rent,
+        beneficiary.key,
+    )?;
+
+    transfer_tokens(token_program, source_token_account, escrow_account, creator, amount, &[])?;
+
+    let vesting = VestingAccount {
+        beneficiary: *beneficiary.key,
+        authority: *creator.key,
+        mint: *mint_account.key,
+        escrow: escrow_key,
+        treasury: *treasury_token_account.key,
+        total_amount: amount,
+        released_amount: 0,
+        start_timestamp,
+        cliff_duration,
+        period_duration,
+        period_count,
+        revocable,
+        revoked: false,
+        bump: vesting_bump,
+    };
+    vesting.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+    emit(VortexEvent::VestingCreated {
+        beneficiary: *beneficiary.key,
+        amount,
+        start_time: start_timestamp,
+        duration: vesting.end_timestamp() - start_timestamp,
+    });
+
+    Ok(())
+}
+
+// Release whatever has unlocked so far to the beneficiary
+pub fn process_claim_vested(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let beneficiary = next_account_info(account_info_iter)?;
+    let vesting_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let beneficiary_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify beneficiary is signer
+    if !beneficiary.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if vesting_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut vesting = VestingAccount::try_from_slice(&vesting_account.data.borrow())?;
+    if vesting.beneficiary != *beneficiary.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+    if vesting.escrow != *escrow_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if vesting.revoked {
+        return Err(VortexError::InvalidState.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let releasable = vesting.releasable_amount(now);
+    if releasable == 0 {
+        return Err(VortexError::InsufficientFunds.into());
+    }
+
+    let bump = [vesting.bump];
+    transfer_tokens(
+        token_program,
+        escrow_account,
+        beneficiary_token_account,
+        vesting_account,
+        releasable,
+        &[&vesting.signer_seeds(&bump)],
+    )?;
+
+    vesting.released_amount += releasable;
+    vesting.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Revoke a grant: pay out what has vested, claw the rest back to the treasury
+pub fn process_revoke_vesting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vesting_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let beneficiary_token_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let program_config = next_account_info(account_info_iter)?;
+
+    // Refuse while the module is paused
+    assert_not_paused(program_config, PausableModule::Token)?;
+
+    // Verify authority is signer
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if vesting_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut vesting = VestingAccount::try_from_slice(&vesting_account.data.borrow())?;
+    if vesting.authority != *authority.key {
+        return Err(VortexError::Unauthorized.into());
+    }
+    if vesting.escrow != *escrow_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !vesting.revocable || vesting.revoked {
+        return Err(VortexError::InvalidState.into());
+    }
+
+    // The payout must land with the beneficiary, the clawback in the
+    // treasury fixed at creation
+    let beneficiary_token = spl_token::state::Account::unpack(&beneficiary_token_account.data.borrow())?;
+    if beneficiary_token.owner != vesting.beneficiary || beneficiary_token.mint != vesting.mint {
+        return Err(VortexError::InvalidAccount.into());
+    }
+    if *treasury_token_account.key != vesting.treasury {
+        return Err(VortexError::InvalidAccount.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let vested = vesting.vested_amount(now);
+    let releasable = vested.saturating_sub(vesting.released_amount);
+    let unvested = vesting.total_amount - vested;
+    let bump = [vesting.bump];
+    let signer_seeds: &[&[u8]] = &vesting.signer_seeds(&bump);
+
+    if releasable > 0 {
+        transfer_tokens(
+            token_program,
+            escrow_account,
+            beneficiary_token_account,
+            vesting_account,
+            releasable,
+            &[signer_seeds],
+        )?;
+    }
+    if unvested > 0 {
+        transfer_tokens(
+            token_program,
+            escrow_account,
+            treasury_token_account,
+            vesting_account,
+            unvested,
+            &[signer_seeds],
+        )?;
+    }
+
+    vesting.released_amount = vested;
+    vesting.total_amount = vested;
+    vesting.revoked = true;
+    vesting.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+    emit(VortexEvent::VestingRevoked {
+        beneficiary: vesting.beneficiary,
+        vested_amount: releasable,
+        clawback_amount: unvested,
+        treasury: *treasury_token_account.key,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{self, account, balance, initialized_config, program, run, token_account, wallet};
+    use crate::token::{process_instruction, TolaInstruction};
+
+    const MONTH: i64 = 30 * 24 * 60 * 60;
+
+    fn schedule(total_amount: u64, cliff_months: i64, period_months: i64, period_count: u32) -> VestingAccount {
+        VestingAccount {
+            beneficiary: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            escrow: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            total_amount,
+            released_amount: 0,
+            start_timestamp: 1_000_000,
+            cliff_duration: cliff_months * MONTH,
+            period_duration: period_months * MONTH,
+            period_count,
+            revocable: true,
+            revoked: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_nothing_vests_before_cliff() {
+        let team = schedule(30_000_000, 6, 1, 48);
+        assert_eq!(team.vested_amount(team.start_timestamp - 1), 0);
+        assert_eq!(team.vested_amount(team.start_timestamp + 6 * MONTH - 1), 0);
+        // The cliff itself opens the first period, it does not release a tranche
+        assert_eq!(team.vested_amount(team.start_timestamp + 6 * MONTH), 0);
+    }
+
+    #[test]
+    fn test_monthly_tranches_after_cliff() {
+        let team = schedule(30_000_000, 6, 1, 48);
+        let tranche = 30_000_000 / 48;
+        assert_eq!(team.vested_amount(team.start_timestamp + 7 * MONTH), tranche);
+        assert_eq!(team.vested_amount(team.start_timestamp + 7 * MONTH + MONTH / 2), tranche);
+        assert_eq!(team.vested_amount(team.start_timestamp + 18 * MONTH), 12 * tranche);
+    }
+
+    #[test]
+    fn test_fully_vested_at_end() {
+        let reserve = schedule(2_500_000, 12, 3, 8);
+        assert_eq!(reserve.end_timestamp(), reserve.start_timestamp + 36 * MONTH);
+        assert_eq!(reserve.vested_amount(reserve.end_timestamp() - 1), 2_500_000 * 7 / 8);
+        assert_eq!(reserve.vested_amount(reserve.end_timestamp()), 2_500_000);
+        assert_eq!(reserve.vested_amount(i64::MAX), 2_500_000);
+    }
+
+    #[test]
+    fn test_last_tranche_carries_remainder() {
+        let grant = schedule(100, 0, 1, 3);
+        assert_eq!(grant.vested_amount(grant.start_timestamp + MONTH), 33);
+        assert_eq!(grant.vested_amount(grant.start_timestamp + 2 * MONTH), 66);
+        assert_eq!(grant.vested_amount(grant.start_timestamp + 3 * MONTH), 100);
+    }
+
+    #[test]
+    fn test_releasable_excludes_released() {
+        let mut partners = schedule(2_500_000, 6, 1, 12);
+        let now = partners.start_timestamp + 9 * MONTH;
+        partners.released_amount = partners.vested_amount(now);
+        assert_eq!(partners.releasable_amount(now), 0);
+        assert_eq!(partners.releasable_amount(now + MONTH), 2_500_000 / 12);
+    }
+
+    #[test]
+    fn test_grant_address_is_keyed_by_grantor() {
+        let program_id = Pubkey::new_unique();
+        let mut grant = schedule(1_000, 0, 1, 10);
+        let (address, bump) = find_vesting_address(&program_id, &grant.authority, &grant.beneficiary);
+        grant.bump = bump;
+
+        // A dust grant from anyone else lands elsewhere and can't squat the beneficiary
+        let (squatter, _) = find_vesting_address(&program_id, &Pubkey::new_unique(), &grant.beneficiary);
+        assert_ne!(squatter, address);
+
+        let bump = [grant.bump];
+        assert_eq!(Pubkey::create_program_address(&grant.signer_seeds(&bump), &program_id).unwrap(), address);
+    }
+
+    #[test]
+    fn test_revoke_pays_vested_and_claws_back_to_treasury() {
+        test_utils::install();
+        let program_id = Pubkey::new_unique();
+        let admin = wallet();
+        let program_config = initialized_config(&admin, &Pubkey::new_unique());
+        let authority = wallet();
+
+        let mut grant = schedule(1_000, 0, 1, 10);
+        grant.authority = *authority.key;
+        grant.start_timestamp = test_utils::NOW - 3 * MONTH;
+        let (vesting_key, bump) = find_vesting_address(&program_id, &grant.authority, &grant.beneficiary);
+        grant.bump = bump;
+        let escrow = token_account(&grant.mint, &vesting_key, 1_000);
+        let treasury = token_account(&grant.mint, &Pubkey::new_unique(), 0);
+        grant.escrow = *escrow.key;
+        grant.treasury = *treasury.key;
+        let vesting_account = account(vesting_key, program_id, 1, grant.try_to_vec().unwrap(), false);
+        let beneficiary_tokens = token_account(&grant.mint, &grant.beneficiary, 0);
+
+        let revoke = |treasury: &AccountInfo<'static>| {
+            let accounts = [
+                authority.clone(),
+                vesting_account.clone(),
+                escrow.clone(),
+                beneficiary_tokens.clone(),
+                treasury.clone(),
+                program(spl_token::id()),
+                program_config.clone(),
+            ];
+            let data = TolaInstruction::RevokeVesting {}.try_to_vec().unwrap();
+            run(process_instruction, &program_id, &accounts, &data)
+        };
+
+        // The authority can't redirect the clawback to itself
+        let own = token_account(&grant.mint, authority.key, 0);
+        assert_eq!(revoke(&own).unwrap_err(), VortexError::InvalidAccount.into());
+
+        revoke(&treasury).unwrap();
+        assert_eq!(balance(&beneficiary_tokens), 300);
+        assert_eq!(balance(&treasury), 700);
+        assert_eq!(balance(&escrow), 0);
+        let vesting = VestingAccount::try_from_slice(&vesting_account.data.borrow()).unwrap();
+        assert!(vesting.revoked);
+        assert_eq!(vesting.total_amount, 300);
+        assert_eq!(vesting.released_amount, 300);
+    }
+}
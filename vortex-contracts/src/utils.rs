@@ -0,0 +1,215 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+};
+use spl_token::instruction::AuthorityType;
+
+// Create an account at a program derived address, signing with its seeds
+pub fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    new_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    owner: &Pubkey,
+    rent: &Rent,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            new_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            owner,
+        ),
+        &[payer.clone(), new_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )
+}
+
+// Create an SPL token account at a program derived address
+#[allow(clippy::too_many_arguments)]
+pub fn create_pda_token_account<'a>(
+    payer: &AccountInfo<'a>,
+    token_account: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    owner: &Pubkey,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    rent: &Rent,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    create_pda_account(
+        payer,
+        token_account,
+        system_program,
+        &spl_token::id(),
+        rent,
+        spl_token::state::Account::LEN,
+        signer_seeds,
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account3(
+            &spl_token::id(),
+            token_account.key,
+            mint.key,
+            owner,
+        )?,
+        &[token_account.clone(), mint.clone(), token_program.clone()],
+    )
+}
+
+// Transfer SPL tokens; `signer_seeds` is empty when the authority signed the transaction
+pub fn transfer_tokens<'a>(
+    token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            source.key,
+            destination.key,
+            authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source.clone(),
+            destination.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+// Transfer lamports out of a system account; `signer_seeds` is empty when the source signed the transaction
+pub fn transfer_lamports<'a>(
+    source: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::transfer(source.key, destination.key, amount),
+        &[source.clone(), destination.clone(), system_program.clone()],
+        signer_seeds,
+    )
+}
+
+// Close an empty SPL token account, sending its rent to `destination`
+pub fn close_token_account<'a>(
+    token_program: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &spl_token::instruction::close_account(
+            &spl_token::id(),
+            account.key,
+            destination.key,
+            authority.key,
+            &[],
+        )?,
+        &[
+            account.clone(),
+            destination.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+// Close an account owned by this program, sending its rent to `receiver`
+pub fn close_program_account(account: &AccountInfo, receiver: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **receiver.try_borrow_mut_lamports()? = receiver
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+// Create a 0-decimal mint, mint a single token to `owner`'s associated token
+// account and drop the mint authority so no second copy can exist
+#[allow(clippy::too_many_arguments)]
+pub fn create_nft<'a>(
+    payer: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    owner_token_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    associated_token_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> ProgramResult {
+    invoke(
+        &system_instruction::create_account(
+            payer.key,
+            mint.key,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[payer.clone(), mint.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(&spl_token::id(), mint.key, payer.key, None, 0)?,
+        &[mint.clone(), token_program.clone()],
+    )?;
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account(
+            payer.key,
+            owner.key,
+            mint.key,
+            &spl_token::id(),
+        ),
+        &[
+            payer.clone(),
+            owner_token_account.clone(),
+            owner.clone(),
+            mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )?;
+    invoke(
+        &spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint.key,
+            owner_token_account.key,
+            payer.key,
+            &[],
+            1,
+        )?,
+        &[mint.clone(), owner_token_account.clone(), payer.clone(), token_program.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::set_authority(
+            &spl_token::id(),
+            mint.key,
+            None,
+            AuthorityType::MintTokens,
+            payer.key,
+            &[],
+        )?,
+        &[mint.clone(), payer.clone(), token_program.clone()],
+    )
+}